@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use typed_key::{Lexer, Parser};
+
+/// A catalog-sized mix of plain, plural, select and tagged messages.
+fn messages() -> Vec<String> {
+    (0..10_000)
+        .flat_map(|i| {
+            [
+                format!("Welcome back, {{name}}! You have {i} new notifications."),
+                format!(
+                    "{{count, plural, offset:1 =0 {{No items}} one {{# item in <b>cart {i}</b>}} other {{{{gender, select, male {{his # items}} female {{her # items}} other {{their # items}}}}}}}}"
+                ),
+                format!("It's '{{quoted}}' text with {{price, number, ::currency/EUR}} on {{when, date, short}} #{i}"),
+                format!(
+                    "{{place, selectordinal, one {{#st}} two {{#nd}} few {{#rd}} other {{#th}}}} place for <a href=\"/p/{i}\">{{user}}</a>"
+                ),
+            ]
+        })
+        .collect()
+}
+
+fn bench(c: &mut Criterion) {
+    let messages = messages();
+    let bytes: usize = messages.iter().map(String::len).sum();
+
+    let mut group = c.benchmark_group("catalog");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    group.sample_size(20);
+    group.bench_function("lex", |b| {
+        b.iter(|| {
+            for message in &messages {
+                black_box(Lexer::new(message).count());
+            }
+        })
+    });
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            for message in &messages {
+                black_box(Parser::new(message).parse().unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench);
+criterion_main!(benches);
@@ -0,0 +1,262 @@
+use crate::dialect::Formatter;
+use crate::parse::{AstNode, Options};
+use crate::span::Span;
+use crate::visit::{walk_options, Visit};
+
+/// An argument a message expects, e.g. `count` in `{count, plural, ...}`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument {
+    pub name: String,
+    pub kind: ArgumentKind,
+}
+
+/// How a message uses an argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentKind {
+    /// `{count, plural, ...}`; `#` shows the count minus `offset`.
+    Plural { offset: u32 },
+    /// `{place, selectordinal, ...}`
+    SelectOrdinal,
+    /// `{gender, select, ...}` with the keys of its options.
+    Select(Vec<String>),
+    /// `{price, number, style}`
+    Number(Option<String>),
+    /// `{when, date, style}`
+    Date(Option<String>),
+    /// `{when, time, style}`
+    Time(Option<String>),
+    /// i18next `{{value, formatter}}` with at least one formatter.
+    Formatted(Vec<Formatter>),
+    /// `{name}` or `{{name}}`.
+    Simple,
+}
+
+impl ArgumentKind {
+    pub fn is_number(&self) -> bool {
+        match self {
+            ArgumentKind::Plural { .. } | ArgumentKind::SelectOrdinal | ArgumentKind::Number(_) => {
+                true
+            }
+            ArgumentKind::Formatted(formatters) => formatters.iter().any(Formatter::is_number),
+            _ => false,
+        }
+    }
+
+    pub fn is_date(&self) -> bool {
+        match self {
+            ArgumentKind::Date(_) | ArgumentKind::Time(_) => true,
+            ArgumentKind::Formatted(formatters) => {
+                !self.is_number() && formatters.iter().any(Formatter::is_date)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn is_list(&self) -> bool {
+        match self {
+            ArgumentKind::Formatted(formatters) => {
+                !self.is_number() && !self.is_date() && formatters.iter().any(Formatter::is_list)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The arguments `node` expects, in order of first use, looking into every
+/// option, tag and recovered error node.
+///
+/// An argument used several times keeps its first typed use; a plain `{name}`
+/// never overrides one. i18next `{{user.name}}` is the argument `user`.
+pub fn arguments(node: &AstNode) -> Vec<Argument> {
+    let mut collector = Collector::default();
+    collector.visit_node(node);
+    collector.arguments
+}
+
+/// `found` refined by the arguments a catalog declares, such as typed ARB
+/// placeholders: a declared kind replaces a plain `{name}`, and declared
+/// arguments the message does not use come last.
+pub fn with_declared(mut found: Vec<Argument>, declared: &[Argument]) -> Vec<Argument> {
+    for argument in declared {
+        match found.iter_mut().find(|found| found.name == argument.name) {
+            Some(found) if found.kind == ArgumentKind::Simple => found.kind = argument.kind.clone(),
+            Some(_) => {}
+            None => found.push(argument.clone()),
+        }
+    }
+    found
+}
+
+#[derive(Default)]
+struct Collector {
+    arguments: Vec<Argument>,
+}
+
+impl Collector {
+    fn add(&mut self, name: &str, kind: ArgumentKind) {
+        match self
+            .arguments
+            .iter_mut()
+            .find(|argument| argument.name == name)
+        {
+            Some(argument) if argument.kind == ArgumentKind::Simple => argument.kind = kind,
+            Some(_) => {}
+            None => self.arguments.push(Argument {
+                name: name.to_string(),
+                kind,
+            }),
+        }
+    }
+}
+
+impl Visit for Collector {
+    fn visit_variable(&mut self, name: &str, _span: Span) {
+        self.add(name, ArgumentKind::Simple);
+    }
+
+    fn visit_plural(&mut self, variable: &str, offset: u32, options: &Options, _span: Span) {
+        self.add(variable, ArgumentKind::Plural { offset });
+        walk_options(self, options);
+    }
+
+    fn visit_select_ordinal(&mut self, variable: &str, options: &Options, _span: Span) {
+        self.add(variable, ArgumentKind::SelectOrdinal);
+        walk_options(self, options);
+    }
+
+    fn visit_select(&mut self, variable: &str, options: &Options, _span: Span) {
+        self.add(
+            variable,
+            ArgumentKind::Select(options.keys().cloned().collect()),
+        );
+        walk_options(self, options);
+    }
+
+    fn visit_number(&mut self, variable: &str, style: Option<&str>, _span: Span) {
+        self.add(variable, ArgumentKind::Number(style.map(str::to_string)));
+    }
+
+    fn visit_date(&mut self, variable: &str, style: Option<&str>, _span: Span) {
+        self.add(variable, ArgumentKind::Date(style.map(str::to_string)));
+    }
+
+    fn visit_time(&mut self, variable: &str, style: Option<&str>, _span: Span) {
+        self.add(variable, ArgumentKind::Time(style.map(str::to_string)));
+    }
+
+    fn visit_interpolation(
+        &mut self,
+        variable: &str,
+        formatters: &[Formatter],
+        _escaped: bool,
+        _span: Span,
+    ) {
+        let root = variable.split('.').next().unwrap_or(variable);
+        let kind = if formatters.is_empty() {
+            ArgumentKind::Simple
+        } else {
+            ArgumentKind::Formatted(formatters.to_vec())
+        };
+        self.add(root, kind);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Parser;
+    use crate::Dialect;
+
+    fn argument(name: &str, kind: ArgumentKind) -> Argument {
+        Argument {
+            name: name.to_string(),
+            kind,
+        }
+    }
+
+    fn found(message: &str) -> Vec<Argument> {
+        let (node, _) = Parser::new(message).parse_recovering();
+        arguments(&node)
+    }
+
+    #[test]
+    fn finds_arguments_in_nested_selects() {
+        assert_eq!(
+            found(
+                "{gender, select, female {{count, plural, offset:1 one {# {item}} other {{place, selectordinal, other {#}}}}} other {<b>{name}</b>}}"
+            ),
+            [
+                argument(
+                    "gender",
+                    ArgumentKind::Select(vec!["female".to_string(), "other".to_string()])
+                ),
+                argument("count", ArgumentKind::Plural { offset: 1 }),
+                argument("item", ArgumentKind::Simple),
+                argument("place", ArgumentKind::SelectOrdinal),
+                argument("name", ArgumentKind::Simple),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_first_typed_use() {
+        let number = || argument("n", ArgumentKind::Number(Some("integer".to_string())));
+        assert_eq!(found("{n} {n, number, integer}"), [number()]);
+        assert_eq!(found("{n, number, integer} {n}"), [number()]);
+        assert_eq!(
+            found("{n, number, integer} {n, plural, other {#}}"),
+            [number()]
+        );
+        assert_eq!(
+            found("{d, date, short} {t, time}"),
+            [
+                argument("d", ArgumentKind::Date(Some("short".to_string()))),
+                argument("t", ArgumentKind::Time(None)),
+            ]
+        );
+    }
+
+    #[test]
+    fn looks_into_recovered_errors() {
+        assert_eq!(
+            found("{n, plural, one {{a}}} {b"),
+            [
+                argument("n", ArgumentKind::Plural { offset: 0 }),
+                argument("a", ArgumentKind::Simple),
+                argument("b", ArgumentKind::Simple),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_i18next_roots_and_formatters() {
+        let node =
+            Parser::with_dialect("{{user.name}} {{user.age}} {{n, number}}", Dialect::I18next)
+                .parse()
+                .unwrap();
+        let found = arguments(&node);
+        assert_eq!(found[0], argument("user", ArgumentKind::Simple));
+        assert_eq!(found.len(), 2);
+        assert!(found[1].kind.is_number());
+        assert!(
+            matches!(&found[1].kind, ArgumentKind::Formatted(formatters) if formatters.len() == 1)
+        );
+    }
+
+    #[test]
+    fn declared_arguments_refine_plain_ones() {
+        let declared = [
+            argument("n", ArgumentKind::Number(None)),
+            argument("count", ArgumentKind::Number(None)),
+            argument("unused", ArgumentKind::Date(None)),
+        ];
+        assert_eq!(
+            with_declared(found("{n} {count, plural, other {#}}"), &declared),
+            [
+                argument("n", ArgumentKind::Number(None)),
+                argument("count", ArgumentKind::Plural { offset: 0 }),
+                argument("unused", ArgumentKind::Date(None)),
+            ]
+        );
+    }
+}
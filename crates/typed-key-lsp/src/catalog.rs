@@ -0,0 +1,979 @@
+use indexmap::IndexMap;
+use serde_json::{Number, Value};
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::arguments::{Argument, ArgumentKind};
+use crate::error::{JsonError, PatternError, PatternErrorKind, PoError};
+use crate::fluent;
+use crate::json;
+use crate::parse::AstNode;
+use crate::plural::{self, PluralCategory};
+use crate::po::PoFile;
+use crate::print::Printer;
+use crate::span::Span;
+use crate::xliff::{self, XliffUnit};
+use crate::Dialect;
+
+/// The file formats catalogs can be written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogFormat {
+    Json,
+    Yaml,
+    /// A gettext catalog of translations.
+    Po,
+    /// A gettext template, whose `msgid`s are the source messages.
+    Pot,
+    /// XLIFF 1.2 or 2.0, with source and target messages.
+    Xliff,
+    /// Flutter's Application Resource Bundle: JSON with `@key` metadata.
+    Arb,
+    /// A Project Fluent resource.
+    Fluent,
+}
+
+impl CatalogFormat {
+    /// The format of `path`, from its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "json" => Some(CatalogFormat::Json),
+            "yml" | "yaml" => Some(CatalogFormat::Yaml),
+            "po" => Some(CatalogFormat::Po),
+            "pot" => Some(CatalogFormat::Pot),
+            "xlf" | "xliff" => Some(CatalogFormat::Xliff),
+            "arb" => Some(CatalogFormat::Arb),
+            "ftl" => Some(CatalogFormat::Fluent),
+            _ => None,
+        }
+    }
+
+    /// The dialect the format's messages are read in, if the format decides
+    /// it: gettext plurals become ICU `plural` arguments, ARB messages are
+    /// ICU, and Fluent resources hold Fluent patterns.
+    pub fn dialect(self) -> Option<Dialect> {
+        match self {
+            CatalogFormat::Json | CatalogFormat::Yaml | CatalogFormat::Xliff => None,
+            CatalogFormat::Po | CatalogFormat::Pot | CatalogFormat::Arb => Some(Dialect::Icu),
+            CatalogFormat::Fluent => Some(Dialect::Fluent),
+        }
+    }
+}
+
+/// The dialect of the catalog at `path`: the one its format implies, or else
+/// `dialect` resolved for its messages.
+pub fn resolve_dialect<'a>(
+    path: &Path,
+    dialect: Dialect,
+    messages: impl IntoIterator<Item = &'a str>,
+) -> Dialect {
+    CatalogFormat::from_path(path)
+        .and_then(CatalogFormat::dialect)
+        .unwrap_or_else(|| dialect.resolve(messages))
+}
+
+/// A message read from a catalog.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub key: String,
+    pub value: Value,
+    /// What the catalog tells translators about the message, e.g. a gettext
+    /// comment.
+    pub note: Option<String>,
+    /// The locale of the message, if the catalog says so for each message
+    /// as XLIFF does; otherwise it is the catalog's locale.
+    pub locale: Option<String>,
+    /// Arguments the catalog declares for the message, such as ARB
+    /// placeholders with their types; see `arguments::with_declared`.
+    pub placeholders: Vec<Argument>,
+    /// Where the message is in the file, for formats that keep track of it:
+    /// JSON, ARB and Fluent.
+    pub location: Option<Location>,
+}
+
+/// The byte spans of a message's key and value in its catalog. JSON keys
+/// and strings include their quotes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Location {
+    pub key: Span,
+    pub value: Span,
+}
+
+/// Reads the messages of a catalog in file order.
+///
+/// Nested JSON and YAML objects become dotted keys: `{"home": {"title":
+/// ".."}}` is `home.title`, and array items are `list[0]`. YAML anchors,
+/// aliases and `<<` merge keys are resolved. A YAML catalog with a single
+/// locale at its root, as Rails writes them (`en: {..}`), is read as that
+/// locale's messages unless `catalog` has another locale.
+///
+/// Gettext entries are keyed by `msgid`, with i18next's context suffix
+/// `msgid_msgctxt` if they have a context, and their comments as notes.
+/// Plural entries become `{count, plural, ..}` messages whose options follow
+/// the `Plural-Forms` header. Fuzzy and untranslated entries are skipped,
+/// except in templates, which read the `msgid`s.
+///
+/// Each XLIFF unit gives a message in the source language and, if it is
+/// translated, one in the target language, keyed by the unit's name or id.
+/// Placeholders are kept as the text they stand for; see `xliff::parse`.
+///
+/// ARB metadata is left out of the keys: the `description` of `@key` is the
+/// note of `key`, and its typed `placeholders` are declared arguments.
+///
+/// Fluent messages and their attributes (`id.attribute`) are keyed as
+/// written, with the `#` comment above them as their note. Terms, which only
+/// other messages can use, and junk are left out.
+///
+/// A locale found in the file, such as a Rails root, a gettext `Language`
+/// header or an ARB `@@locale`, is recorded in `catalog` if it has none.
+pub fn read_messages(path: &Path, catalog: &mut CatalogPath) -> io::Result<Vec<Message>> {
+    let content = fs::read_to_string(path)?;
+    parse_messages(path, &content, catalog)
+}
+
+/// Reads the messages of a catalog at `path` whose `content` is already
+/// loaded; see `read_messages`.
+pub fn parse_messages(
+    path: &Path,
+    content: &str,
+    catalog: &mut CatalogPath,
+) -> io::Result<Vec<Message>> {
+    let format = CatalogFormat::from_path(path).unwrap_or(CatalogFormat::Json);
+    match format {
+        CatalogFormat::Json => {
+            let document = json::parse(content).map_err(|error| invalid_json(content, error))?;
+            Ok(flatten(&document.value, Some(&document)))
+        }
+        CatalogFormat::Yaml => {
+            let mut yaml: serde_yaml::Value = serde_yaml::from_str(content)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            yaml.apply_merge()
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            Ok(flatten(
+                &unwrap_locale_root(yaml_to_json(yaml), catalog),
+                None,
+            ))
+        }
+        CatalogFormat::Po | CatalogFormat::Pot => {
+            read_po(content, format == CatalogFormat::Pot, catalog)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+        }
+        CatalogFormat::Xliff => {
+            let units = xliff::parse(content)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            Ok(xliff_messages(units))
+        }
+        CatalogFormat::Arb => {
+            let document = json::parse(content).map_err(|error| invalid_json(content, error))?;
+            Ok(arb_messages(&document, catalog))
+        }
+        CatalogFormat::Fluent => {
+            let (entries, _) = fluent::parse_resource(content);
+            Ok(entries
+                .into_iter()
+                .filter(|entry| !entry.is_term())
+                .map(|entry| Message {
+                    key: entry.key,
+                    value: Value::String(entry.source),
+                    note: entry.comment,
+                    locale: None,
+                    placeholders: Vec::new(),
+                    location: Some(Location {
+                        key: entry.key_span,
+                        value: entry.span,
+                    }),
+                })
+                .collect())
+        }
+    }
+}
+
+/// Reads the string messages of a catalog by key, in file order; see
+/// `read_messages`. Where a catalog has a key in several locales, the first
+/// message, such as the XLIFF source, is kept.
+pub fn read_catalog(
+    path: &Path,
+    catalog: &mut CatalogPath,
+) -> io::Result<IndexMap<String, String>> {
+    Ok(read_messages(path, catalog)?
+        .into_iter()
+        .fold(IndexMap::new(), |mut messages, message| {
+            if let Value::String(value) = message.value {
+                messages.entry(message.key).or_insert(value);
+            }
+            messages
+        }))
+}
+
+/// `error` with the line and column it is at, as `serde_json` reports them.
+fn invalid_json(content: &str, error: JsonError) -> io::Error {
+    let before = &content[..error.span.start];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |newline| newline + 1) + 1;
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} at line {} column {}", error, line, column),
+    )
+}
+
+/// The messages of a nested catalog, located in `document` if it was read
+/// from JSON.
+fn flatten(value: &Value, document: Option<&json::JsonDocument>) -> Vec<Message> {
+    let mut messages = Vec::new();
+    flatten_into(value, String::new(), "", document, &mut messages);
+    messages
+}
+
+fn flatten_into(
+    value: &Value,
+    prefix: String,
+    pointer: &str,
+    document: Option<&json::JsonDocument>,
+    messages: &mut Vec<Message>,
+) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let pointer = format!("{}/{}", pointer, json::pointer_segment(key));
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_into(value, key, &pointer, document, messages);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                let pointer = format!("{}/{}", pointer, index);
+                let key = format!("{}[{}]", prefix, index);
+                flatten_into(value, key, &pointer, document, messages);
+            }
+        }
+        // An empty YAML document.
+        Value::Null if prefix.is_empty() => {}
+        value => messages.push(Message {
+            key: prefix,
+            value: value.clone(),
+            note: None,
+            locale: None,
+            placeholders: Vec::new(),
+            location: document.and_then(|document| document.location(pointer)),
+        }),
+    }
+}
+
+fn read_po(
+    content: &str,
+    template: bool,
+    catalog: &mut CatalogPath,
+) -> Result<Vec<Message>, PoError> {
+    let file = PoFile::parse(content)?;
+    if catalog.locale.is_none() {
+        catalog.locale = file
+            .header("Language")
+            .and_then(|language| parse_locale(&language.to_ascii_lowercase()));
+    }
+    let forms = file.plural_forms()?;
+    // Without a locale the forms are matched with the English categories.
+    let categories = forms.categories(catalog.locale.as_deref().unwrap_or("en"));
+    let mut messages = Vec::new();
+    for entry in file.entries {
+        let value = if template {
+            match entry.plural_id {
+                Some(plural_id) => plural_message(
+                    &[entry.id.clone(), plural_id],
+                    &[(PluralCategory::One, 0), (PluralCategory::Other, 1)],
+                ),
+                None => po_message(&entry.id),
+            }
+        } else {
+            if entry.is_fuzzy() || !entry.is_translated() {
+                continue;
+            }
+            match entry.plural_id {
+                Some(_) => plural_message(&entry.strings, &categories),
+                None => po_message(&entry.strings[0]),
+            }
+        };
+        let key = match entry.context {
+            Some(context) => format!("{}_{}", entry.id, context),
+            None => entry.id,
+        };
+        let note = (!entry.comments.is_empty()).then(|| entry.comments.join("\n"));
+        messages.push(Message {
+            key,
+            value: Value::String(value),
+            note,
+            locale: None,
+            placeholders: Vec::new(),
+            location: None,
+        });
+    }
+    Ok(messages)
+}
+
+fn xliff_messages(units: Vec<XliffUnit>) -> Vec<Message> {
+    let mut messages = Vec::new();
+    for unit in units {
+        let note = (!unit.notes.is_empty()).then(|| unit.notes.join("\n"));
+        messages.push(Message {
+            key: unit.key.clone(),
+            value: Value::String(unit.source),
+            note: note.clone(),
+            locale: unit.source_language.as_deref().map(normalize_locale),
+            placeholders: Vec::new(),
+            location: None,
+        });
+        if let Some(target) = unit.target.filter(|target| !target.is_empty()) {
+            messages.push(Message {
+                key: unit.key,
+                value: Value::String(target),
+                note,
+                locale: unit.target_language.as_deref().map(normalize_locale),
+                placeholders: Vec::new(),
+                location: None,
+            });
+        }
+    }
+    messages
+}
+
+fn arb_messages(document: &json::JsonDocument, catalog: &mut CatalogPath) -> Vec<Message> {
+    let Value::Object(map) = &document.value else {
+        return Vec::new();
+    };
+    if catalog.locale.is_none() {
+        catalog.locale = map
+            .get("@@locale")
+            .and_then(Value::as_str)
+            .map(normalize_locale);
+    }
+    map.iter()
+        .filter(|(key, _)| !key.starts_with('@'))
+        .map(|(key, value)| {
+            let metadata = map.get(&format!("@{}", key));
+            let note = metadata
+                .and_then(|metadata| metadata.get("description"))
+                .and_then(Value::as_str)
+                .map(str::to_string);
+            let placeholders = metadata
+                .and_then(|metadata| metadata.get("placeholders"))
+                .and_then(Value::as_object)
+                .into_iter()
+                .flatten()
+                .map(|(name, placeholder)| Argument {
+                    name: name.clone(),
+                    kind: placeholder_kind(placeholder),
+                })
+                .collect();
+            Message {
+                key: key.clone(),
+                value: value.clone(),
+                note,
+                locale: None,
+                placeholders,
+                location: document.location(&format!("/{}", json::pointer_segment(key))),
+            }
+        })
+        .collect()
+}
+
+/// `int`, `double` and `num` placeholders are numbers and `DateTime` ones
+/// dates, formatted with their `format`; others, such as `String`, are
+/// interpolated as they are.
+fn placeholder_kind(placeholder: &Value) -> ArgumentKind {
+    let format = placeholder
+        .get("format")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    match placeholder.get("type").and_then(Value::as_str) {
+        Some("int" | "double" | "num") => ArgumentKind::Number(format),
+        Some("DateTime") => ArgumentKind::Date(format),
+        _ => ArgumentKind::Simple,
+    }
+}
+
+/// A gettext plural entry as an ICU `{count, plural, ...}` message, each form
+/// read as by `po_message`; a form the entry lacks reads as empty.
+fn plural_message(strings: &[String], categories: &[(PluralCategory, usize)]) -> String {
+    let options = categories
+        .iter()
+        .map(|(category, index)| {
+            let string = strings.get(*index).map(String::as_str).unwrap_or_default();
+            (category.to_string(), po_nodes(string))
+        })
+        .collect();
+    Printer::new().print(&AstNode::Plural {
+        variable: "count".to_string(),
+        offset: 0,
+        options,
+        span: Span::default(),
+    })
+}
+
+/// A gettext string as an ICU message: `{name}` placeholders are arguments
+/// and everything else is literal text, quoted where ICU would read it as
+/// syntax, e.g. a `#` that would be the count in a plural form.
+fn po_message(string: &str) -> String {
+    Printer::new().print(&AstNode::Root(po_nodes(string), Span::default()))
+}
+
+fn po_nodes(string: &str) -> Vec<AstNode> {
+    let mut nodes = Vec::new();
+    let mut rest = string;
+    while !rest.is_empty() {
+        let placeholder = rest.strip_prefix('{').and_then(|after| {
+            let (name, _) = after.split_once('}')?;
+            let valid = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_');
+            valid.then_some(name)
+        });
+        let (node, length) = match placeholder {
+            Some(name) => (
+                AstNode::Variable(name.to_string(), Span::default()),
+                name.len() + 2,
+            ),
+            None => {
+                // A `{` that starts no placeholder is text.
+                let skip = usize::from(rest.starts_with('{'));
+                let end = rest[skip..].find('{').map_or(rest.len(), |end| end + skip);
+                (AstNode::Text(rest[..end].to_string(), Span::default()), end)
+            }
+        };
+        nodes.push(node);
+        rest = &rest[length..];
+    }
+    nodes
+}
+
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+    match value {
+        serde_yaml::Value::Null => Value::Null,
+        serde_yaml::Value::Bool(value) => Value::Bool(value),
+        serde_yaml::Value::Number(number) => {
+            if let Some(number) = number.as_i64() {
+                Value::from(number)
+            } else if let Some(number) = number.as_u64() {
+                Value::from(number)
+            } else {
+                number
+                    .as_f64()
+                    .and_then(Number::from_f64)
+                    .map_or(Value::Null, Value::Number)
+            }
+        }
+        serde_yaml::Value::String(value) => Value::String(value),
+        serde_yaml::Value::Sequence(items) => {
+            Value::Array(items.into_iter().map(yaml_to_json).collect())
+        }
+        serde_yaml::Value::Mapping(mapping) => Value::Object(
+            mapping
+                .into_iter()
+                .filter_map(|(key, value)| Some((yaml_key(key)?, yaml_to_json(value))))
+                .collect(),
+        ),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+/// YAML keys can be any scalar; `1:` and `true:` are read as strings, and
+/// keys that are not scalars are dropped.
+fn yaml_key(key: serde_yaml::Value) -> Option<String> {
+    match key {
+        serde_yaml::Value::String(key) => Some(key),
+        serde_yaml::Value::Number(key) => Some(key.to_string()),
+        serde_yaml::Value::Bool(key) => Some(key.to_string()),
+        serde_yaml::Value::Tagged(tagged) => yaml_key(tagged.value),
+        _ => None,
+    }
+}
+
+fn unwrap_locale_root(value: Value, catalog: &mut CatalogPath) -> Value {
+    let Value::Object(map) = value else {
+        return value;
+    };
+    let root = match map.iter().next() {
+        Some((key, messages)) if map.len() == 1 && messages.is_object() => parse_locale(key)
+            .filter(|locale| catalog.locale.as_ref().is_none_or(|known| known == locale)),
+        _ => None,
+    };
+    let Some(locale) = root else {
+        return Value::Object(map);
+    };
+    catalog.locale = Some(locale);
+    map.into_iter()
+        .next()
+        .map_or(Value::Null, |(_, messages)| messages)
+}
+
+/// What a catalog's path says about it.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CatalogPath {
+    pub locale: Option<String>,
+    pub namespace: Option<String>,
+}
+
+/// A catalog layout such as `{locale}/{namespace}.json` or
+/// `messages_{locale}.json`, relative to the translations directory.
+///
+/// Each `/`-separated part of the pattern matches one part of a path. A
+/// placeholder matches at least one character up to the next `/` or `.`,
+/// `{locale}` only a locale such as `fr` or `pt_BR`, and the rest of the
+/// pattern must match exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogPattern {
+    segments: Vec<Vec<Piece>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(String),
+    Locale,
+    Namespace,
+}
+
+impl CatalogPattern {
+    pub fn new(pattern: &str) -> Result<Self, PatternError> {
+        let mut segments = vec![Vec::new()];
+        let mut seen = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            let start = pattern.len() - rest.len();
+            let pieces = segments.last_mut().expect("there is always a segment");
+            if let Some(after) = rest.strip_prefix('/') {
+                segments.push(Vec::new());
+                rest = after;
+                continue;
+            }
+            if let Some(after) = rest.strip_prefix('{') {
+                let Some(end) = after.find('}') else {
+                    return Err(PatternError::new(
+                        PatternErrorKind::UnclosedPlaceholder,
+                        Span::new(start, pattern.len()),
+                    ));
+                };
+                let name = &after[..end];
+                let span = Span::new(start, start + end + 2);
+                let piece = match name {
+                    "locale" => Piece::Locale,
+                    "namespace" => Piece::Namespace,
+                    _ => {
+                        return Err(PatternError::new(
+                            PatternErrorKind::UnknownPlaceholder {
+                                name: name.to_string(),
+                            },
+                            span,
+                        ))
+                    }
+                };
+                if seen.contains(&name) {
+                    return Err(PatternError::new(
+                        PatternErrorKind::RepeatedPlaceholder {
+                            name: name.to_string(),
+                        },
+                        span,
+                    ));
+                }
+                if matches!(pieces.last(), Some(Piece::Locale | Piece::Namespace)) {
+                    return Err(PatternError::new(
+                        PatternErrorKind::AdjacentPlaceholders,
+                        span,
+                    ));
+                }
+                seen.push(name);
+                pieces.push(piece);
+                rest = &after[end + 1..];
+                continue;
+            }
+            let end = rest.find(['/', '{']).unwrap_or(rest.len());
+            pieces.push(Piece::Text(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+        Ok(CatalogPattern { segments })
+    }
+
+    /// The locale and namespace of `path`, relative to the translations
+    /// directory, or `None` if the pattern does not match it.
+    pub fn matches(&self, path: &Path) -> Option<CatalogPath> {
+        let parts = path
+            .components()
+            .map(|component| match component {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if parts.len() != self.segments.len() {
+            return None;
+        }
+        let mut catalog = CatalogPath::default();
+        for (pieces, part) in self.segments.iter().zip(parts) {
+            match_pieces(pieces, part, &mut catalog)?;
+        }
+        Some(catalog)
+    }
+}
+
+/// Matches one path part, trying the shortest value for each placeholder
+/// first; `{locale}` takes only values that read as a locale.
+fn match_pieces(pieces: &[Piece], text: &str, catalog: &mut CatalogPath) -> Option<()> {
+    let Some((piece, rest)) = pieces.split_first() else {
+        return text.is_empty().then_some(());
+    };
+    match piece {
+        Piece::Text(literal) => match_pieces(rest, text.strip_prefix(literal.as_str())?, catalog),
+        Piece::Locale | Piece::Namespace => {
+            let longest = text.find('.').unwrap_or(text.len());
+            // Values end on a character boundary, however many bytes it is.
+            let ends = text.char_indices().skip(1).map(|(index, _)| index);
+            ends.chain([text.len()])
+                .take_while(|&end| end <= longest)
+                .filter(|&end| end > 0)
+                .find_map(|end| {
+                    let value = &text[..end];
+                    let locale = match piece {
+                        Piece::Locale => Some(parse_locale(&value.to_ascii_lowercase())?),
+                        _ => None,
+                    };
+                    match_pieces(rest, &text[end..], catalog)?;
+                    match locale {
+                        Some(locale) => catalog.locale = Some(locale),
+                        None => catalog.namespace = Some(value.to_string()),
+                    }
+                    Some(())
+                })
+        }
+    }
+}
+
+/// Finds the catalogs below `dir`, sorted by path: the files `pattern`
+/// matches, or without one every file in a known `CatalogFormat`, with its
+/// locale and namespace guessed by `guess_catalog_path`.
+pub fn find_catalogs(dir: &Path, pattern: Option<&CatalogPattern>) -> Vec<(PathBuf, CatalogPath)> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let path = entry.into_path();
+            let relative = path.strip_prefix(dir).unwrap_or(&path);
+            let catalog = match pattern {
+                Some(pattern) => pattern.matches(relative)?,
+                None => {
+                    CatalogFormat::from_path(relative)?;
+                    guess_catalog_path(relative)
+                }
+            };
+            Some((path, catalog))
+        })
+        .collect()
+}
+
+/// What a catalog's path below the translations directory says without a
+/// pattern. The locale is the nearest directory named like one, with the
+/// file name as the namespace (`fr/common.json`, `pt_BR/app.json`), or else
+/// the end of the file name (`fr.json`, `messages_fr.json`,
+/// `messages.en-US.json`).
+pub fn guess_catalog_path(path: &Path) -> CatalogPath {
+    let directories = path.parent().into_iter().flat_map(Path::iter).rev();
+    let from_directory = directories
+        .filter_map(|directory| directory.to_str())
+        .find_map(parse_locale);
+    let stem = path.file_stem().and_then(|stem| stem.to_str());
+    match (from_directory, stem) {
+        (Some(locale), stem) => CatalogPath {
+            locale: Some(locale),
+            namespace: stem.map(str::to_string),
+        },
+        (None, Some(stem)) => {
+            let parts: Vec<_> = stem.split(['_', '.', '-']).collect();
+            // Longest first, so `messages_en_US` is `en-US` rather than `US`.
+            let locale = (1..=parts.len().min(3))
+                .rev()
+                .find_map(|count| parse_locale(&parts[parts.len() - count..].join("-")));
+            CatalogPath {
+                locale,
+                namespace: None,
+            }
+        }
+        (None, None) => CatalogPath::default(),
+    }
+}
+
+/// Reads a locale such as `en`, `pt_BR` or `zh-Hant-TW` in BCP 47 form:
+/// a two-letter language, or a three-letter one CLDR has plural rules for,
+/// then an optional script and region.
+fn parse_locale(candidate: &str) -> Option<String> {
+    let mut subtags = candidate.split(['-', '_']);
+    let language = subtags.next()?;
+    let is_language = language.chars().all(|c| c.is_ascii_lowercase())
+        && (language.len() == 2 || (language.len() == 3 && plural::has_rules(language)));
+    if !is_language {
+        return None;
+    }
+    let mut locale = language.to_string();
+    let mut subtags = subtags.peekable();
+    if let Some(script) = subtags
+        .next_if(|subtag| subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        locale.push('-');
+        locale.push_str(&script[..1].to_ascii_uppercase());
+        locale.push_str(&script[1..].to_ascii_lowercase());
+    }
+    if let Some(region) = subtags.next_if(|subtag| {
+        (subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+            || (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit()))
+    }) {
+        locale.push('-');
+        locale.push_str(&region.to_ascii_uppercase());
+    }
+    subtags.next().is_none().then_some(locale)
+}
+
+/// `pt_br` and `pt-BR` are the same locale; anything that does not look like
+/// one is kept as written.
+pub(crate) fn normalize_locale(locale: &str) -> String {
+    parse_locale(&locale.to_ascii_lowercase()).unwrap_or_else(|| locale.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Parser;
+    use crate::render::Renderer;
+
+    fn matches(pattern: &str, path: &str) -> Option<CatalogPath> {
+        CatalogPattern::new(pattern)
+            .unwrap()
+            .matches(Path::new(path))
+    }
+
+    fn catalog(locale: &str, namespace: Option<&str>) -> Option<CatalogPath> {
+        Some(CatalogPath {
+            locale: Some(locale.to_string()),
+            namespace: namespace.map(str::to_string),
+        })
+    }
+
+    #[test]
+    fn matches_non_ascii_segments() {
+        assert_eq!(
+            matches("{locale}/{namespace}.json", "fr/économie.json"),
+            catalog("fr", Some("économie"))
+        );
+        assert_eq!(
+            matches("{namespace}_{locale}.json", "主要_ja.json"),
+            catalog("ja", Some("主要"))
+        );
+        assert_eq!(matches("{locale}.json", "é.json"), None);
+        assert_eq!(
+            matches("{locale}/{namespace}.json", "日本語/主要.json"),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_paths_the_pattern_does_not_match() {
+        assert_eq!(matches("{locale}.json", "fr.yaml"), None);
+        assert_eq!(matches("{locale}.json", ".json"), None);
+        assert_eq!(matches("{locale}/{namespace}.json", "fr.json"), None);
+        assert_eq!(matches("messages_{locale}.json", "strings_é.json"), None);
+    }
+
+    #[test]
+    fn matches_several_placeholders() {
+        assert_eq!(
+            matches("{namespace}-{locale}.json", "checkout-pt_BR.json"),
+            catalog("pt-BR", Some("checkout"))
+        );
+        assert_eq!(
+            matches("{locale}/{namespace}.json", "de/common.json"),
+            catalog("de", Some("common"))
+        );
+        // The first placeholder takes the shortest value that leaves a
+        // locale for `{locale}`.
+        assert_eq!(
+            matches("{namespace}_{locale}.json", "app_main_fr.json"),
+            catalog("fr", Some("app_main"))
+        );
+        assert_eq!(
+            matches("{namespace}_{locale}.json", "app_main_pt_BR.json"),
+            catalog("pt-BR", Some("app_main"))
+        );
+        assert_eq!(matches("{namespace}_{locale}.json", "app_main.json"), None);
+    }
+
+    /// The messages of `content` read as the file `path`, with what the
+    /// file says about its catalog.
+    fn read(path: &str, content: &str) -> (Vec<(String, Value)>, CatalogPath) {
+        let mut catalog = CatalogPath::default();
+        let messages = parse_messages(Path::new(path), content, &mut catalog).unwrap();
+        let messages = messages
+            .into_iter()
+            .map(|message| (message.key, message.value))
+            .collect();
+        (messages, catalog)
+    }
+
+    fn strings(messages: &[(&str, &str)]) -> Vec<(String, Value)> {
+        messages
+            .iter()
+            .map(|(key, value)| (key.to_string(), Value::from(*value)))
+            .collect()
+    }
+
+    #[test]
+    fn reads_yaml_anchors_merges_and_block_scalars() {
+        let yaml = concat!(
+            "defaults: &defaults\n",
+            "  save: Save\n",
+            "  cancel: Cancel\n",
+            "dialog:\n",
+            "  <<: *defaults\n",
+            "  cancel: Close\n",
+            "again: *defaults\n",
+            "literal: |\n",
+            "  Line one\n",
+            "  Line two\n",
+            "folded: >-\n",
+            "  One\n",
+            "  sentence\n",
+            "list:\n",
+            "  - first\n",
+            "1: one\n",
+        );
+        let (messages, catalog) = read("messages.yml", yaml);
+        assert_eq!(
+            messages,
+            strings(&[
+                ("defaults.save", "Save"),
+                ("defaults.cancel", "Cancel"),
+                ("dialog.cancel", "Close"),
+                ("dialog.save", "Save"),
+                ("again.save", "Save"),
+                ("again.cancel", "Cancel"),
+                ("literal", "Line one\nLine two\n"),
+                ("folded", "One sentence"),
+                ("list[0]", "first"),
+                ("1", "one"),
+            ])
+        );
+        assert_eq!(catalog, CatalogPath::default());
+    }
+
+    #[test]
+    fn reads_the_locale_root_of_yaml() {
+        let yaml = "en:\n  home:\n    title: Welcome\n";
+        let (messages, catalog) = read("messages.yaml", yaml);
+        assert_eq!(messages, strings(&[("home.title", "Welcome")]));
+        assert_eq!(catalog.locale.as_deref(), Some("en"));
+
+        // A root that is not the locale of the path is a key like any other.
+        let mut catalog = CatalogPath {
+            locale: Some("fr".to_string()),
+            namespace: None,
+        };
+        let messages = parse_messages(Path::new("fr.yaml"), yaml, &mut catalog).unwrap();
+        assert_eq!(messages[0].key, "en.home.title");
+        assert_eq!(catalog.locale.as_deref(), Some("fr"));
+
+        let (messages, _) = read("app.yml", "home:\n  title: Welcome\n");
+        assert_eq!(messages, strings(&[("home.title", "Welcome")]));
+    }
+
+    /// Renders the message `key` of a PO file with `count`.
+    fn render_po(content: &str, key: &str, count: i64) -> String {
+        let messages =
+            parse_messages(Path::new("fr.po"), content, &mut CatalogPath::default()).unwrap();
+        let message = messages.iter().find(|message| message.key == key).unwrap();
+        let Value::String(value) = &message.value else {
+            panic!("{} is not a string", key);
+        };
+        let node = Parser::new(value).parse().unwrap();
+        let arguments = [
+            ("count".to_string(), count.into()),
+            ("name".to_string(), "Ann".into()),
+        ];
+        Renderer::new("fr")
+            .render(&node, &arguments.into_iter().collect())
+            .unwrap()
+    }
+
+    #[test]
+    fn po_text_keeps_its_meaning_in_icu() {
+        let po = concat!(
+            "msgid \"\"\n",
+            "msgstr \"Language: fr\\n\"\n",
+            "\"Plural-Forms: nplurals=2; plural=(n > 1);\\n\"\n\n",
+            "msgctxt \"rank\"\n",
+            "msgid \"item\"\n",
+            "msgid_plural \"items\"\n",
+            "msgstr[0] \"#{count} d'or\"\n",
+            "msgstr[1] \"{count} {a b} '{'\"\n\n",
+            "msgid \"Hi {name}\"\n",
+            "msgstr \"Salut {name}, l'été #1 '{'\"\n",
+        );
+        assert_eq!(render_po(po, "item_rank", 1), "#1 d'or");
+        assert_eq!(render_po(po, "item_rank", 5), "5 {a b} '{'");
+        assert_eq!(render_po(po, "Hi {name}", 0), "Salut Ann, l'été #1 '{'");
+    }
+
+    #[test]
+    fn reads_arb_metadata() {
+        let arb = r#"{
+            "@@locale": "pt_BR",
+            "greeting": "Hi {name}, {count} new since {when}",
+            "@greeting": {
+                "description": "Shown on the home page",
+                "placeholders": {
+                    "name": {"type": "String"},
+                    "count": {"type": "int", "format": "compact"},
+                    "when": {"type": "DateTime", "format": "yMd"},
+                    "ratio": {"type": "double"},
+                    "other": {}
+                }
+            },
+            "bare": "Plain"
+        }"#;
+        let mut catalog = CatalogPath::default();
+        let messages = parse_messages(Path::new("app.arb"), arb, &mut catalog).unwrap();
+        assert_eq!(catalog.locale.as_deref(), Some("pt-BR"));
+        let keys: Vec<_> = messages
+            .iter()
+            .map(|message| message.key.as_str())
+            .collect();
+        assert_eq!(keys, ["greeting", "bare"]);
+
+        let greeting = &messages[0];
+        assert_eq!(greeting.note.as_deref(), Some("Shown on the home page"));
+        let kinds: Vec<_> = greeting
+            .placeholders
+            .iter()
+            .map(|argument| (argument.name.as_str(), argument.kind.clone()))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("name", ArgumentKind::Simple),
+                ("count", ArgumentKind::Number(Some("compact".to_string()))),
+                ("when", ArgumentKind::Date(Some("yMd".to_string()))),
+                ("ratio", ArgumentKind::Number(None)),
+                ("other", ArgumentKind::Simple),
+            ]
+        );
+        let location = greeting.location.unwrap();
+        assert_eq!(&arb[location.key.start..location.key.end], "\"greeting\"");
+        assert_eq!(messages[1].note, None);
+        assert!(messages[1].placeholders.is_empty());
+
+        // The locale of the path wins over `@@locale`.
+        let mut catalog = CatalogPath {
+            locale: Some("fr".to_string()),
+            namespace: None,
+        };
+        parse_messages(Path::new("app_fr.arb"), arb, &mut catalog).unwrap();
+        assert_eq!(catalog.locale.as_deref(), Some("fr"));
+    }
+}
@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+
+/// The interpolation syntax a catalog is written in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Dialect {
+    /// ICU MessageFormat: `{name}`, `{count, plural, ...}`.
+    #[default]
+    Icu,
+    /// i18next: `{{name}}`, `{{price, currency(USD)}}`, `{{- html}}`.
+    I18next,
+    /// Project Fluent: `{ $name }`, `{ $count -> *[other] ... }`.
+    Fluent,
+    /// Pick ICU or i18next by looking at the messages.
+    Auto,
+}
+
+impl Dialect {
+    /// Resolves `Auto` to a concrete dialect for a whole catalog: i18next if
+    /// any message contains an i18next interpolation, ICU otherwise.
+    pub fn resolve<'a>(self, messages: impl IntoIterator<Item = &'a str>) -> Dialect {
+        match self {
+            Dialect::Auto => {
+                if messages.into_iter().any(has_i18next_interpolation) {
+                    Dialect::I18next
+                } else {
+                    Dialect::Icu
+                }
+            }
+            dialect => dialect,
+        }
+    }
+}
+
+/// A formatter applied to an i18next interpolation, e.g. `currency(USD)` in
+/// `{{price, currency(USD)}}`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Formatter {
+    pub name: String,
+    pub options: Option<String>,
+}
+
+impl Formatter {
+    /// Built-in i18next formatters that expect a number.
+    pub fn is_number(&self) -> bool {
+        matches!(self.name.as_str(), "number" | "currency" | "relativetime")
+    }
+
+    /// Built-in i18next formatters that expect a date.
+    pub fn is_date(&self) -> bool {
+        self.name == "datetime"
+    }
+
+    /// Built-in i18next formatters that expect a list.
+    pub fn is_list(&self) -> bool {
+        self.name == "list"
+    }
+
+    pub(crate) fn parse(formatter: &str) -> Self {
+        let formatter = formatter.trim();
+        match formatter.split_once('(') {
+            Some((name, options)) => Formatter {
+                name: name.trim().to_string(),
+                options: Some(options.trim_end_matches(')').trim().to_string()),
+            },
+            None => Formatter {
+                name: formatter.to_string(),
+                options: None,
+            },
+        }
+    }
+}
+
+/// Whether `message` contains `{{name}}`, `{{- name}}` or `{{name, format}}`.
+/// ICU nesting such as `other {{count} days}` does not count.
+fn has_i18next_interpolation(message: &str) -> bool {
+    let mut rest = message;
+    while let Some(start) = rest.find("{{") {
+        rest = &rest[start + 2..];
+        let Some(end) = rest.find('}') else {
+            return false;
+        };
+        let inner = rest[..end].trim_start();
+        let inner = inner.strip_prefix('-').unwrap_or(inner).trim();
+        let name = inner.split(',').next().unwrap_or_default().trim();
+        if rest[end..].starts_with("}}")
+            && !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '$' | '-'))
+        {
+            return true;
+        }
+    }
+    false
+}
@@ -0,0 +1,514 @@
+use std::fmt::Display;
+
+use miette::{Diagnostic, LabeledSpan, SourceSpan};
+use thiserror::Error;
+
+use crate::span::Span;
+
+/// An error in a message, pointing at the part of the message that caused it.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind}")]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ParseErrorKind {
+    #[error("unclosed argument")]
+    UnclosedArgument,
+    #[error("empty argument")]
+    EmptyArgument,
+    #[error("argument name `{name}` contains braces")]
+    BracedArgumentName { name: String },
+    #[error("{kind} argument `{variable}` has no options")]
+    MissingOptions {
+        variable: String,
+        kind: &'static str,
+    },
+    #[error("option `{key}` is never closed")]
+    UnclosedOption { key: String },
+    #[error("option `{key}` has no message")]
+    MissingOptionBody { key: String },
+    #[error("invalid {kind} category `{category}`")]
+    InvalidCategory {
+        category: String,
+        kind: &'static str,
+    },
+    #[error("invalid plural offset `{offset}`")]
+    InvalidOffset { offset: String },
+    #[error("{kind} argument `{variable}` has no `other` option")]
+    MissingOther {
+        variable: String,
+        kind: &'static str,
+    },
+    #[error("{kind} argument `{variable}` is missing {categories} for locale `{locale}`")]
+    MissingCategories {
+        variable: String,
+        kind: &'static str,
+        locale: String,
+        categories: String,
+    },
+    #[error("tag `{name}` is never closed")]
+    UnclosedTag { name: String },
+    #[error("expected `</{expected}>`, found `</{found}>`")]
+    MismatchedTag { expected: String, found: String },
+    #[error("closing tag `</{name}>` has no opening tag")]
+    UnexpectedClosingTag { name: String },
+    #[error("expected a message `id = ...`, a term `-id = ...` or a comment")]
+    InvalidEntry,
+    #[error("expected a variable, literal, reference or function call")]
+    InvalidExpression,
+    #[error("string literal is never closed")]
+    UnclosedString,
+    #[error("select expression on `{variable}` has no default variant")]
+    MissingDefaultVariant { variable: String },
+    #[error("select expression on `{variable}` has more than one default variant")]
+    RepeatedDefaultVariant { variable: String },
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, span: Span) -> Self {
+        ParseError { kind, span }
+    }
+
+    /// Suggestion for fixing the message.
+    pub fn help(&self) -> &'static str {
+        match &self.kind {
+            ParseErrorKind::UnclosedArgument => {
+                "add the missing `}`, or quote a literal brace as '{'"
+            }
+            ParseErrorKind::EmptyArgument => "put the argument name between the braces: `{name}`",
+            ParseErrorKind::BracedArgumentName { .. } => {
+                "ICU arguments take one pair of braces: `{name}`; for i18next's `{{name}}` set `dialect: \"i18next\"`"
+            }
+            ParseErrorKind::MissingOptions { .. } => {
+                "add options after the argument type, e.g. `one {# item} other {# items}`"
+            }
+            ParseErrorKind::UnclosedOption { .. } => "add the missing `}` after the option message",
+            ParseErrorKind::MissingOptionBody { .. } => {
+                "follow every option key with a message in braces, e.g. `other {...}`"
+            }
+            ParseErrorKind::InvalidCategory { .. } => {
+                "use one of zero, one, two, few, many, other or an exact match such as =0"
+            }
+            ParseErrorKind::InvalidOffset { .. } => "the offset must be a whole number: `offset:1`",
+            ParseErrorKind::MissingOther { .. } => "add an `other {...}` option as the fallback",
+            ParseErrorKind::MissingCategories { .. } => {
+                "add an option for every plural category the locale uses"
+            }
+            ParseErrorKind::UnclosedTag { .. } => {
+                "add the matching closing tag, or write `<tag/>` for a tag without content"
+            }
+            ParseErrorKind::MismatchedTag { .. } => {
+                "close tags in the reverse order they were opened"
+            }
+            ParseErrorKind::UnexpectedClosingTag { .. } => {
+                "remove the closing tag or add the opening tag"
+            }
+            ParseErrorKind::InvalidEntry => {
+                "start the line with an identifier and `=`, or indent it to continue the entry above"
+            }
+            ParseErrorKind::InvalidExpression => {
+                "write `{ $name }`, `{ \"text\" }`, `{ -term }` or `{ NUMBER($n) }`; literal braces are `{ \"{\" }`"
+            }
+            ParseErrorKind::UnclosedString => "add the missing `\"` before the end of the line",
+            ParseErrorKind::MissingDefaultVariant { .. } => {
+                "mark the fallback variant with `*`, e.g. `*[other]`"
+            }
+            ParseErrorKind::RepeatedDefaultVariant { .. } => {
+                "keep the `*` on one variant only"
+            }
+        }
+    }
+
+    /// The diagnostic code, e.g. `typed_key::unclosed_tag`.
+    pub fn code(&self) -> &'static str {
+        match &self.kind {
+            ParseErrorKind::UnclosedArgument => "typed_key::unclosed_argument",
+            ParseErrorKind::EmptyArgument => "typed_key::empty_argument",
+            ParseErrorKind::BracedArgumentName { .. } => "typed_key::braced_argument_name",
+            ParseErrorKind::MissingOptions { .. } => "typed_key::missing_options",
+            ParseErrorKind::UnclosedOption { .. } => "typed_key::unclosed_option",
+            ParseErrorKind::MissingOptionBody { .. } => "typed_key::missing_option_body",
+            ParseErrorKind::InvalidCategory { .. } => "typed_key::invalid_category",
+            ParseErrorKind::InvalidOffset { .. } => "typed_key::invalid_offset",
+            ParseErrorKind::MissingOther { .. } => "typed_key::missing_other",
+            ParseErrorKind::MissingCategories { .. } => "typed_key::missing_categories",
+            ParseErrorKind::UnclosedTag { .. } => "typed_key::unclosed_tag",
+            ParseErrorKind::MismatchedTag { .. } => "typed_key::mismatched_tag",
+            ParseErrorKind::UnexpectedClosingTag { .. } => "typed_key::unexpected_closing_tag",
+            ParseErrorKind::InvalidEntry => "typed_key::invalid_entry",
+            ParseErrorKind::InvalidExpression => "typed_key::invalid_expression",
+            ParseErrorKind::UnclosedString => "typed_key::unclosed_string",
+            ParseErrorKind::MissingDefaultVariant { .. } => "typed_key::missing_default_variant",
+            ParseErrorKind::RepeatedDefaultVariant { .. } => "typed_key::repeated_default_variant",
+        }
+    }
+}
+
+impl Diagnostic for ParseError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(ParseError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(ParseError::help(self)))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.kind.to_string()),
+            self.span,
+        ))))
+    }
+}
+
+/// An error rendering a message, pointing at the argument that caused it.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind}")]
+pub struct RenderError {
+    pub kind: RenderErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum RenderErrorKind {
+    #[error("no value for argument `{name}`")]
+    MissingArgument { name: String },
+    #[error("argument `{name}` must be a number, got `{value}`")]
+    NotANumber { name: String, value: String },
+    #[error("{kind} argument `{name}` can't be rendered")]
+    Unsupported { name: String, kind: &'static str },
+}
+
+impl RenderError {
+    pub fn new(kind: RenderErrorKind, span: Span) -> Self {
+        RenderError { kind, span }
+    }
+
+    /// Suggestion for fixing the arguments.
+    pub fn help(&self) -> &'static str {
+        match &self.kind {
+            RenderErrorKind::MissingArgument { .. } => {
+                "pass a value for every argument the message uses"
+            }
+            RenderErrorKind::NotANumber { .. } => {
+                "plural, selectordinal and number arguments need a finite numeric value"
+            }
+            RenderErrorKind::Unsupported { .. } => {
+                "dates and times need locale data typed-key doesn't have; preview the message with a plain `{name}` instead"
+            }
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match &self.kind {
+            RenderErrorKind::MissingArgument { .. } => "typed_key::missing_argument",
+            RenderErrorKind::NotANumber { .. } => "typed_key::not_a_number",
+            RenderErrorKind::Unsupported { .. } => "typed_key::unsupported_argument",
+        }
+    }
+}
+
+impl Diagnostic for RenderError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(RenderError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(RenderError::help(self)))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.kind.to_string()),
+            self.span,
+        ))))
+    }
+}
+
+/// An error reading a JSON AST, pointing at the offending value with a JSON
+/// pointer such as `/ast/children/0/options/one`.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind} at `{path}`")]
+pub struct SchemaError {
+    pub kind: SchemaErrorKind,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SchemaErrorKind {
+    #[error("unsupported AST schema version {version}")]
+    UnsupportedVersion { version: String },
+    #[error("missing field `{field}`")]
+    MissingField { field: String },
+    #[error("expected {expected}")]
+    InvalidValue { expected: &'static str },
+    #[error("unknown node type `{name}`")]
+    UnknownNodeType { name: String },
+    #[error("unknown error code `{code}`")]
+    UnknownErrorCode { code: String },
+}
+
+impl SchemaError {
+    pub fn new(kind: SchemaErrorKind, path: String) -> Self {
+        SchemaError { kind, path }
+    }
+
+    /// Suggestion for fixing the document.
+    pub fn help(&self) -> &'static str {
+        match &self.kind {
+            SchemaErrorKind::UnsupportedVersion { .. } => {
+                "export the AST again with the typed-key version that reads it"
+            }
+            SchemaErrorKind::MissingField { .. } | SchemaErrorKind::InvalidValue { .. } => {
+                "the document must follow the schema described in `typed_key::schema`"
+            }
+            SchemaErrorKind::UnknownNodeType { .. } => {
+                "use one of the node types described in `typed_key::schema`"
+            }
+            SchemaErrorKind::UnknownErrorCode { .. } => {
+                "use a diagnostic code reported by the parser, e.g. `typed_key::unclosed_argument`"
+            }
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match &self.kind {
+            SchemaErrorKind::UnsupportedVersion { .. } => "typed_key::unsupported_version",
+            SchemaErrorKind::MissingField { .. } => "typed_key::missing_field",
+            SchemaErrorKind::InvalidValue { .. } => "typed_key::invalid_value",
+            SchemaErrorKind::UnknownNodeType { .. } => "typed_key::unknown_node_type",
+            SchemaErrorKind::UnknownErrorCode { .. } => "typed_key::unknown_error_code",
+        }
+    }
+}
+
+impl Diagnostic for SchemaError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(SchemaError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(SchemaError::help(self)))
+    }
+}
+
+/// An error in a catalog path pattern such as `{locale}/{namespace}.json`,
+/// pointing at the part of the pattern that caused it.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind}")]
+pub struct PatternError {
+    pub kind: PatternErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PatternErrorKind {
+    #[error("unknown placeholder `{{{name}}}`")]
+    UnknownPlaceholder { name: String },
+    #[error("placeholder is never closed")]
+    UnclosedPlaceholder,
+    #[error("`{{{name}}}` appears more than once")]
+    RepeatedPlaceholder { name: String },
+    #[error("placeholders must be separated by text")]
+    AdjacentPlaceholders,
+}
+
+impl PatternError {
+    pub fn new(kind: PatternErrorKind, span: Span) -> Self {
+        PatternError { kind, span }
+    }
+
+    /// Suggestion for fixing the pattern.
+    pub fn help(&self) -> &'static str {
+        match &self.kind {
+            PatternErrorKind::UnknownPlaceholder { .. } => "use `{locale}` or `{namespace}`",
+            PatternErrorKind::UnclosedPlaceholder => "add the missing `}`",
+            PatternErrorKind::RepeatedPlaceholder { .. } => {
+                "a path has one locale and one namespace; remove the repeated placeholder"
+            }
+            PatternErrorKind::AdjacentPlaceholders => {
+                "put a separator between them, e.g. `{namespace}.{locale}.json`"
+            }
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match &self.kind {
+            PatternErrorKind::UnknownPlaceholder { .. } => "typed_key::unknown_placeholder",
+            PatternErrorKind::UnclosedPlaceholder => "typed_key::unclosed_placeholder",
+            PatternErrorKind::RepeatedPlaceholder { .. } => "typed_key::repeated_placeholder",
+            PatternErrorKind::AdjacentPlaceholders => "typed_key::adjacent_placeholders",
+        }
+    }
+}
+
+impl Diagnostic for PatternError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(PatternError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(PatternError::help(self)))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.kind.to_string()),
+            self.span,
+        ))))
+    }
+}
+
+/// An error in a gettext PO or POT file, pointing at the line or entry
+/// that caused it.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind}")]
+pub struct PoError {
+    pub kind: PoErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum PoErrorKind {
+    #[error("expected a comment, a keyword such as `msgid` or a quoted string")]
+    UnexpectedLine,
+    #[error("string is never closed")]
+    UnterminatedString,
+    #[error("entry has no `msgstr`")]
+    MissingMsgstr,
+    #[error("cannot read `Plural-Forms: {forms}`")]
+    InvalidPluralForms { forms: String },
+    #[error("`Plural-Forms` nests more than {limit} levels deep")]
+    PluralFormsTooDeep { limit: usize },
+}
+
+impl PoError {
+    pub fn new(kind: PoErrorKind, span: Span) -> Self {
+        PoError { kind, span }
+    }
+
+    /// Suggestion for fixing the file.
+    pub fn help(&self) -> &'static str {
+        match &self.kind {
+            PoErrorKind::UnexpectedLine => {
+                "strings are quoted, and each line starts with `#`, a keyword or `\"`"
+            }
+            PoErrorKind::UnterminatedString => "add the missing `\"` at the end of the line",
+            PoErrorKind::MissingMsgstr => "add `msgstr \"\"` to leave the entry untranslated",
+            PoErrorKind::InvalidPluralForms { .. } => {
+                "write it as gettext does, e.g. `nplurals=2; plural=(n != 1);`"
+            }
+            PoErrorKind::PluralFormsTooDeep { .. } => {
+                "check the formula for stray `(`; gettext formulas need only a few levels"
+            }
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match &self.kind {
+            PoErrorKind::UnexpectedLine => "typed_key::unexpected_po_line",
+            PoErrorKind::UnterminatedString => "typed_key::unterminated_string",
+            PoErrorKind::MissingMsgstr => "typed_key::missing_msgstr",
+            PoErrorKind::InvalidPluralForms { .. } => "typed_key::invalid_plural_forms",
+            PoErrorKind::PluralFormsTooDeep { .. } => "typed_key::plural_forms_too_deep",
+        }
+    }
+}
+
+impl Diagnostic for PoError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(PoError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(PoError::help(self)))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.kind.to_string()),
+            self.span,
+        ))))
+    }
+}
+
+/// An error in a JSON catalog, pointing at the character that caused it.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind}")]
+pub struct JsonError {
+    pub kind: JsonErrorKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum JsonErrorKind {
+    #[error("expected {expected}")]
+    Unexpected { expected: &'static str },
+    #[error("string is never closed")]
+    UnterminatedString,
+    #[error("invalid string `{string}`")]
+    InvalidString { string: String },
+    #[error("invalid number `{number}`")]
+    InvalidNumber { number: String },
+    #[error("nested more than {limit} levels deep")]
+    TooDeep { limit: usize },
+}
+
+impl JsonError {
+    pub fn new(kind: JsonErrorKind, span: Span) -> Self {
+        JsonError { kind, span }
+    }
+
+    /// Suggestion for fixing the file.
+    pub fn help(&self) -> &'static str {
+        match &self.kind {
+            JsonErrorKind::Unexpected { .. } => {
+                "check for a missing or extra comma, and quote keys with `\"`"
+            }
+            JsonErrorKind::UnterminatedString => "add the missing `\"`",
+            JsonErrorKind::InvalidString { .. } => {
+                "escape control characters, and write other escapes as `\\n`, `\\\"` or `\\u00e9`"
+            }
+            JsonErrorKind::InvalidNumber { .. } => "write numbers as `42`, `-1.5` or `1e3`",
+            JsonErrorKind::TooDeep { .. } => {
+                "flatten the catalog, or check that this is a translation file"
+            }
+        }
+    }
+
+    fn code(&self) -> &'static str {
+        match &self.kind {
+            JsonErrorKind::Unexpected { .. } => "typed_key::unexpected_json",
+            JsonErrorKind::UnterminatedString => "typed_key::unterminated_string",
+            JsonErrorKind::InvalidString { .. } => "typed_key::invalid_string",
+            JsonErrorKind::InvalidNumber { .. } => "typed_key::invalid_number",
+            JsonErrorKind::TooDeep { .. } => "typed_key::json_too_deep",
+        }
+    }
+}
+
+impl Diagnostic for JsonError {
+    fn code<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(JsonError::code(self)))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(JsonError::help(self)))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        Some(Box::new(std::iter::once(LabeledSpan::new_with_span(
+            Some(self.kind.to_string()),
+            self.span,
+        ))))
+    }
+}
+
+impl From<Span> for SourceSpan {
+    fn from(span: Span) -> Self {
+        SourceSpan::new(span.start.into(), span.len())
+    }
+}
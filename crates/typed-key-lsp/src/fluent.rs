@@ -0,0 +1,1015 @@
+//! Project Fluent resources (`.ftl`).
+//!
+//! ```text
+//! # Shown above the inbox.
+//! emails = { $count ->
+//!     [one] You have one email
+//!    *[other] You have { $count } emails
+//! }
+//!     .title = Inbox
+//! -brand = Firefox
+//! ```
+//!
+//! Patterns are read into the same tree as ICU messages: `$variables` are
+//! variables, `NUMBER()` and `DATETIME()` calls are number and date
+//! arguments, and select expressions are `plural`, `selectordinal` or
+//! `select` arguments depending on their selector and variant keys. Message
+//! and term references are kept as the text Fluent shows when it cannot
+//! resolve them, e.g. `{-brand}`.
+
+use crate::error::{ParseError, ParseErrorKind};
+use crate::parse::{AstNode, Options};
+use crate::plural::PluralCategory;
+use crate::span::Span;
+
+/// A message, term or attribute of a resource.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FluentEntry {
+    /// `id`, `-term` or `id.attribute`.
+    pub key: String,
+    /// Where the id, or the attribute name, is in the resource.
+    pub key_span: Span,
+    /// The pattern as written, from after the `=` to the end of its last
+    /// line; see `parse_pattern`.
+    pub source: String,
+    /// Where `source` is in the resource.
+    pub span: Span,
+    /// The `#` comment right above the message or term.
+    pub comment: Option<String>,
+}
+
+impl FluentEntry {
+    /// Terms and their attributes can only be referenced from other
+    /// messages.
+    pub fn is_term(&self) -> bool {
+        self.key.starts_with('-')
+    }
+}
+
+/// Reads the messages, terms and attributes of a resource in file order.
+/// Lines that are none of these are skipped with an error, as Fluent skips
+/// junk.
+pub fn parse_resource(input: &str) -> (Vec<FluentEntry>, Vec<ParseError>) {
+    let lines = lines(input);
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+    let mut comment: Vec<&str> = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let (start, line) = lines[index];
+        let line_span = Span::new(start, start + line.len());
+        index += 1;
+        if line.trim().is_empty() {
+            comment.clear();
+            continue;
+        }
+        if let Some(text) = line.strip_prefix('#') {
+            // `##` and `###` comments are about groups of messages and the
+            // whole resource.
+            if text.starts_with('#') {
+                comment.clear();
+            } else {
+                comment.push(text.strip_prefix(' ').unwrap_or(text));
+            }
+            continue;
+        }
+        let Some((id, value_start)) = entry_start(line) else {
+            // Junk runs up to the next line that can start an entry or a
+            // comment.
+            let mut end = index;
+            while end < lines.len()
+                && !lines[end]
+                    .1
+                    .starts_with(|c: char| c == '#' || c == '-' || c.is_ascii_alphabetic())
+            {
+                end += 1;
+            }
+            while end > index && lines[end - 1].1.trim().is_empty() {
+                end -= 1;
+            }
+            let (last_start, last_line) = lines[end - 1];
+            errors.push(ParseError::new(
+                ParseErrorKind::InvalidEntry,
+                Span::new(start, last_start + last_line.len()),
+            ));
+            index = end;
+            comment.clear();
+            continue;
+        };
+
+        // The entry goes on over indented lines and lines that continue a
+        // select expression, leaving out the blank lines after it.
+        let first = index - 1;
+        let mut end = index;
+        while end < lines.len() && continues(lines[end].1) {
+            end += 1;
+        }
+        while end > index && lines[end - 1].1.trim().is_empty() {
+            end -= 1;
+        }
+        index = end;
+
+        // The value, then each attribute: its key and where it is, where its
+        // pattern starts and where its line starts.
+        let id_span = Span::new(start, start + id.len());
+        let mut parts = vec![(id.to_string(), id_span, start + value_start, start)];
+        for &(line_start, line) in &lines[first + 1..end] {
+            if let Some((name, name_start, pattern_start)) = attribute_start(line) {
+                let name_start = line_start + name_start;
+                parts.push((
+                    format!("{}.{}", id, name),
+                    Span::new(name_start, name_start + name.len()),
+                    line_start + pattern_start,
+                    line_start,
+                ));
+            }
+        }
+        let (last_start, last_line) = lines[end - 1];
+        let entry_end = last_start + last_line.len();
+        let note = (!comment.is_empty()).then(|| comment.join("\n"));
+        let mut found = false;
+        for (i, (key, key_span, pattern_start, _)) in parts.iter().enumerate() {
+            let pattern_end = parts.get(i + 1).map_or(entry_end, |next| next.3);
+            let source = input[*pattern_start..pattern_end.max(*pattern_start)].trim_end();
+            if source.trim().is_empty() {
+                continue;
+            }
+            found = true;
+            entries.push(FluentEntry {
+                key: key.clone(),
+                key_span: *key_span,
+                source: source.to_string(),
+                span: Span::new(*pattern_start, pattern_start + source.len()),
+                comment: note.clone(),
+            });
+        }
+        if !found {
+            errors.push(ParseError::new(ParseErrorKind::InvalidEntry, line_span));
+        }
+        comment.clear();
+    }
+    (entries, errors)
+}
+
+/// Parses a Fluent pattern, such as the `source` of a `FluentEntry`, with
+/// spans relative to `input`. Broken placeables become `AstNode::Error`
+/// nodes, as in `Parser::parse_recovering`.
+///
+/// Lines after the first are dedented by their common indentation, and
+/// trailing whitespace is dropped, as Fluent does.
+pub fn parse_pattern(input: &str) -> (AstNode, Vec<ParseError>) {
+    let mut parser = PatternParser {
+        input,
+        position: 0,
+        errors: Vec::new(),
+    };
+    let mut nodes = parser.pattern(false);
+    loop {
+        // Either trailing whitespace or a line starting with `[`, `*`, `.`
+        // or `}` outside of a select expression.
+        parser.skip_blank();
+        if parser.position == input.len() {
+            break;
+        }
+        let start = parser.position;
+        parser.skip_line();
+        let error = ParseError::new(
+            ParseErrorKind::InvalidExpression,
+            Span::new(start, parser.position),
+        );
+        parser.errors.push(error.clone());
+        nodes.push(AstNode::Error {
+            error,
+            recovered: None,
+            span: Span::new(start, parser.position),
+        });
+        nodes.extend(parser.pattern(false));
+    }
+    let mut errors = parser.errors;
+    errors.sort_by_key(|error| error.span.start);
+    (AstNode::Root(nodes, Span::new(0, input.len())), errors)
+}
+
+/// Each line of `input` with its byte offset, without its line ending.
+fn lines(input: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for line in input.split_inclusive('\n') {
+        let text = line.strip_suffix('\n').unwrap_or(line);
+        lines.push((start, text.strip_suffix('\r').unwrap_or(text)));
+        start += line.len();
+    }
+    lines
+}
+
+/// The id of a `id = ...` or `-term = ...` line and where its pattern
+/// starts.
+fn entry_start(line: &str) -> Option<(&str, usize)> {
+    let term = usize::from(line.starts_with('-'));
+    let length = identifier_length(&line[term..]);
+    if length == 0 {
+        return None;
+    }
+    let length = term + length;
+    let rest = line[length..].trim_start_matches(' ').strip_prefix('=')?;
+    let value = rest.trim_start_matches(' ');
+    Some((&line[..length], line.len() - value.len()))
+}
+
+/// The name of an indented `.attribute = ...` line, where the name starts
+/// and where its pattern starts.
+fn attribute_start(line: &str) -> Option<(&str, usize, usize)> {
+    let trimmed = line.trim_start_matches(' ');
+    if trimmed.len() == line.len() {
+        return None;
+    }
+    let name = trimmed.strip_prefix('.')?;
+    let length = identifier_length(name);
+    if length == 0 {
+        return None;
+    }
+    let rest = name[length..].trim_start_matches(' ').strip_prefix('=')?;
+    let value = rest.trim_start_matches(' ');
+    Some((
+        &name[..length],
+        line.len() - name.len(),
+        line.len() - value.len(),
+    ))
+}
+
+/// Whether `line` belongs to the entry above it.
+fn continues(line: &str) -> bool {
+    line.trim().is_empty() || line.starts_with([' ', '}', '[', '*'])
+}
+
+/// The length of the identifier at the start of `text`: a letter, then
+/// letters, digits, `_` and `-`.
+fn identifier_length(text: &str) -> usize {
+    if !text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return 0;
+    }
+    text.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(text.len())
+}
+
+/// An inline expression, before it is turned into nodes.
+#[derive(Debug, Clone)]
+enum Expression {
+    String(String),
+    Number(String),
+    Variable(String),
+    /// A message or term reference, written as in the resource:
+    /// `menu.title`, `-brand`.
+    Reference(String),
+    Function {
+        name: String,
+        positional: Vec<Expression>,
+        /// Option values are literals, kept as written.
+        named: Vec<(String, String)>,
+    },
+    Placeable(Vec<AstNode>),
+}
+
+impl Expression {
+    /// The variable a selector or function call reads, with whether
+    /// `NUMBER(.., type: "ordinal")` asks for ordinal categories.
+    fn variable(&self) -> Option<(&str, bool)> {
+        match self {
+            Expression::Variable(variable) => Some((variable, false)),
+            Expression::Function {
+                name,
+                positional,
+                named,
+            } if name == "NUMBER" => match positional.first() {
+                Some(Expression::Variable(variable)) => Some((
+                    variable,
+                    named.iter().any(|(k, v)| k == "type" && v == "\"ordinal\""),
+                )),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// How the expression reads in errors.
+    fn describe(&self) -> String {
+        match self {
+            Expression::String(value) => format!("\"{}\"", value),
+            Expression::Number(number) => number.clone(),
+            Expression::Variable(variable) => format!("${}", variable),
+            Expression::Reference(id) => id.clone(),
+            Expression::Function {
+                name, positional, ..
+            } => match positional.first() {
+                Some(first) => format!("{}({})", name, first.describe()),
+                None => format!("{}()", name),
+            },
+            Expression::Placeable(_) => "{ ... }".to_string(),
+        }
+    }
+}
+
+/// The positional and named arguments of a call.
+type Arguments = (Vec<Expression>, Vec<(String, String)>);
+
+/// The key of a variant, whether it is the `*` default, and its pattern.
+type Variant = (String, bool, Vec<AstNode>);
+
+/// A piece of a pattern before indentation is removed.
+enum Piece {
+    Text(String, Span),
+    /// Line breaks followed by `indent` spaces.
+    Break {
+        newlines: usize,
+        indent: usize,
+        span: Span,
+    },
+    Node(AstNode),
+}
+
+struct PatternParser<'a> {
+    input: &'a str,
+    position: usize,
+    errors: Vec<ParseError>,
+}
+
+impl PatternParser<'_> {
+    fn current(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn rest(&self) -> &str {
+        &self.input[self.position..]
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.current() == Some(c) {
+            self.position += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_blank(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start_matches([' ', '\n', '\r']).len();
+    }
+
+    /// Moves to the end of the line, before its line ending.
+    fn skip_line(&mut self) {
+        let rest = self.rest();
+        self.position += rest.find(['\r', '\n']).unwrap_or(rest.len());
+    }
+
+    fn identifier(&mut self) -> Option<String> {
+        let length = identifier_length(self.rest());
+        if length == 0 {
+            return None;
+        }
+        let identifier = self.rest()[..length].to_string();
+        self.position += length;
+        Some(identifier)
+    }
+
+    fn error(&self, kind: ParseErrorKind, start: usize) -> ParseError {
+        let end = self
+            .position
+            .max(start + self.current().map_or(0, char::len_utf8));
+        ParseError::new(kind, Span::new(start, end.min(self.input.len())))
+    }
+
+    /// Text and placeables up to the end of the input or a line starting
+    /// with `[`, `*`, `.` or `}`. In a variant, a `}` also ends the pattern.
+    fn pattern(&mut self, in_variant: bool) -> Vec<AstNode> {
+        let mut pieces = Vec::new();
+        while let Some(c) = self.current() {
+            match c {
+                '{' => pieces.extend(self.placeable().into_iter().map(Piece::Node)),
+                '}' if in_variant => break,
+                '}' => {
+                    let start = self.position;
+                    self.position += 1;
+                    let error = ParseError::new(
+                        ParseErrorKind::InvalidExpression,
+                        Span::new(start, self.position),
+                    );
+                    self.errors.push(error.clone());
+                    pieces.push(Piece::Node(AstNode::Error {
+                        error,
+                        recovered: None,
+                        span: Span::new(start, self.position),
+                    }));
+                }
+                '\n' | '\r' => {
+                    let start = self.position;
+                    let mut end = start;
+                    let mut newlines = 0;
+                    let mut indent = 0;
+                    for c in self.input[start..].chars() {
+                        match c {
+                            '\n' => {
+                                newlines += 1;
+                                indent = 0;
+                            }
+                            '\r' => {}
+                            ' ' => indent += 1,
+                            _ => break,
+                        }
+                        end += 1;
+                    }
+                    match self.input[end..].chars().next() {
+                        None | Some('[' | '*' | '.' | '}') => break,
+                        Some(_) => {
+                            pieces.push(Piece::Break {
+                                newlines,
+                                indent,
+                                span: Span::new(start, end),
+                            });
+                            self.position = end;
+                        }
+                    }
+                }
+                _ => {
+                    let start = self.position;
+                    let length = self
+                        .rest()
+                        .find(['{', '}', '\n', '\r'])
+                        .unwrap_or(self.rest().len());
+                    self.position += length;
+                    let text = &self.input[start..self.position];
+                    pieces.push(Piece::Text(
+                        text.to_string(),
+                        Span::new(start, self.position),
+                    ));
+                }
+            }
+        }
+        dedent(pieces)
+    }
+
+    /// A `{ ... }` placeable. Select expressions on something other than a
+    /// variable give the nodes of their default variant.
+    fn placeable(&mut self) -> Vec<AstNode> {
+        let start = self.position;
+        self.position += 1;
+        match self.placeable_body(start) {
+            Ok(nodes) => nodes,
+            Err(error) => {
+                self.recover(start);
+                self.errors.push(error.clone());
+                vec![AstNode::Error {
+                    error,
+                    recovered: None,
+                    span: Span::new(start, self.position),
+                }]
+            }
+        }
+    }
+
+    fn placeable_body(&mut self, start: usize) -> Result<Vec<AstNode>, ParseError> {
+        self.skip_blank();
+        match self.current() {
+            None => return Err(self.error(ParseErrorKind::UnclosedArgument, start)),
+            Some('}') => {
+                self.position += 1;
+                return Err(self.error(ParseErrorKind::EmptyArgument, start));
+            }
+            _ => {}
+        }
+        let expression = self.inline_expression()?;
+        self.skip_blank();
+        let variants = if self.rest().starts_with("->") {
+            self.position += 2;
+            Some(self.variants(&expression)?)
+        } else {
+            None
+        };
+        self.skip_blank();
+        if !self.eat('}') {
+            return Err(self.error(ParseErrorKind::UnclosedArgument, start));
+        }
+        let span = Span::new(start, self.position);
+        Ok(match variants {
+            Some(variants) => select(expression, variants, span),
+            None => inline(expression, span),
+        })
+    }
+
+    /// Moves past the `}` that closes the placeable at `start`, or to the
+    /// end of the input.
+    fn recover(&mut self, start: usize) {
+        let mut depth = 0;
+        let mut in_string = false;
+        let mut chars = self.input[start..].char_indices().peekable();
+        while let Some((offset, c)) = chars.next() {
+            match c {
+                '\\' if in_string => {
+                    chars.next();
+                }
+                '"' => in_string = !in_string,
+                '\n' => in_string = false,
+                '{' if !in_string => depth += 1,
+                '}' if !in_string => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.position = start + offset + 1;
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.position = self.input.len();
+    }
+
+    fn inline_expression(&mut self) -> Result<Expression, ParseError> {
+        let start = self.position;
+        match self.current() {
+            Some('"') => self.string_literal().map(Expression::String),
+            Some('{') => Ok(Expression::Placeable(self.placeable())),
+            Some('$') => {
+                self.position += 1;
+                match self.identifier() {
+                    Some(name) => Ok(Expression::Variable(name)),
+                    None => Err(self.error(ParseErrorKind::InvalidExpression, start)),
+                }
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' && self.is_number_after_minus() => {
+                self.position += 1;
+                let length = self
+                    .rest()
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                    .unwrap_or(self.rest().len());
+                self.position += length;
+                Ok(Expression::Number(
+                    self.input[start..self.position].to_string(),
+                ))
+            }
+            Some('-') => {
+                self.position += 1;
+                let Some(id) = self.identifier() else {
+                    return Err(self.error(ParseErrorKind::InvalidExpression, start));
+                };
+                let reference = format!("-{}{}", id, self.attribute_accessor()?);
+                // Arguments passed to a term only change its own variants.
+                if self.current() == Some('(') {
+                    self.call_arguments()?;
+                }
+                Ok(Expression::Reference(reference))
+            }
+            Some(c) if c.is_ascii_alphabetic() => {
+                let id = self.identifier().unwrap_or_default();
+                if self.current() == Some('(') {
+                    let (positional, named) = self.call_arguments()?;
+                    return Ok(Expression::Function {
+                        name: id,
+                        positional,
+                        named,
+                    });
+                }
+                let attribute = self.attribute_accessor()?;
+                Ok(Expression::Reference(format!("{}{}", id, attribute)))
+            }
+            _ => Err(self.error(ParseErrorKind::InvalidExpression, start)),
+        }
+    }
+
+    fn is_number_after_minus(&self) -> bool {
+        self.rest()[1..].starts_with(|c: char| c.is_ascii_digit())
+    }
+
+    /// `.attribute` after a reference, or nothing.
+    fn attribute_accessor(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        if !self.eat('.') {
+            return Ok(String::new());
+        }
+        match self.identifier() {
+            Some(attribute) => Ok(format!(".{}", attribute)),
+            None => Err(self.error(ParseErrorKind::InvalidExpression, start)),
+        }
+    }
+
+    fn string_literal(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        self.position += 1;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.current() else {
+                return Err(self.error(ParseErrorKind::UnclosedString, start));
+            };
+            self.position += c.len_utf8();
+            match c {
+                '"' => return Ok(value),
+                '\n' | '\r' => {
+                    self.position -= 1;
+                    return Err(self.error(ParseErrorKind::UnclosedString, start));
+                }
+                '\\' => {
+                    let escape_start = self.position - 1;
+                    let digits = match self.current() {
+                        Some(c @ ('\\' | '"')) => {
+                            self.position += 1;
+                            value.push(c);
+                            continue;
+                        }
+                        Some('u') => 4,
+                        Some('U') => 6,
+                        _ => {
+                            return Err(self.error(ParseErrorKind::InvalidExpression, escape_start))
+                        }
+                    };
+                    let hex = self.rest().get(1..1 + digits).unwrap_or_default();
+                    let decoded = u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+                    match decoded {
+                        Some(c) if hex.len() == digits => {
+                            self.position += 1 + digits;
+                            value.push(c);
+                        }
+                        _ => {
+                            self.position += 1;
+                            return Err(self.error(ParseErrorKind::InvalidExpression, escape_start));
+                        }
+                    }
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    /// `(positional, name: "value")` after a function or term name.
+    fn call_arguments(&mut self) -> Result<Arguments, ParseError> {
+        let start = self.position;
+        self.position += 1;
+        let mut positional = Vec::new();
+        let mut named = Vec::new();
+        loop {
+            self.skip_blank();
+            if self.eat(')') {
+                break;
+            }
+            let argument_start = self.position;
+            let name = self.identifier();
+            self.skip_blank();
+            match name {
+                Some(name) if self.eat(':') => {
+                    self.skip_blank();
+                    let value_start = self.position;
+                    match self.inline_expression()? {
+                        Expression::String(_) | Expression::Number(_) => {
+                            named.push((name, self.input[value_start..self.position].to_string()))
+                        }
+                        _ => return Err(self.error(ParseErrorKind::InvalidExpression, value_start)),
+                    }
+                }
+                _ => {
+                    self.position = argument_start;
+                    positional.push(self.inline_expression()?);
+                }
+            }
+            self.skip_blank();
+            if self.eat(')') {
+                break;
+            }
+            if !self.eat(',') {
+                return Err(self.error(ParseErrorKind::InvalidExpression, start));
+            }
+        }
+        Ok((positional, named))
+    }
+
+    /// The `[key] pattern` variants after `->`, with whether each is the
+    /// `*` default.
+    fn variants(&mut self, selector: &Expression) -> Result<Vec<Variant>, ParseError> {
+        let start = self.position;
+        let mut variants = Vec::new();
+        loop {
+            self.skip_blank();
+            let variant_start = self.position;
+            let default = self.eat('*');
+            if !self.eat('[') {
+                if default {
+                    return Err(self.error(ParseErrorKind::InvalidExpression, variant_start));
+                }
+                break;
+            }
+            self.skip_blank();
+            let key_start = self.position;
+            let key = match self.identifier() {
+                Some(key) => key,
+                None => match self.inline_expression() {
+                    Ok(Expression::Number(number)) => number,
+                    _ => {
+                        self.position = key_start;
+                        return Err(self.error(ParseErrorKind::InvalidExpression, key_start));
+                    }
+                },
+            };
+            self.skip_blank();
+            if !self.eat(']') {
+                return Err(self.error(ParseErrorKind::UnclosedOption { key }, variant_start));
+            }
+            let rest = self.rest();
+            self.position += rest.len() - rest.trim_start_matches(' ').len();
+            let nodes = self.pattern(true);
+            variants.push((key, default, nodes));
+        }
+        let variable = selector.describe();
+        match variants.iter().filter(|(_, default, _)| *default).count() {
+            _ if variants.is_empty() => Err(self.error(
+                ParseErrorKind::MissingOptions {
+                    variable,
+                    kind: "select",
+                },
+                start,
+            )),
+            0 => Err(self.error(ParseErrorKind::MissingDefaultVariant { variable }, start)),
+            1 => Ok(variants),
+            _ => Err(self.error(ParseErrorKind::RepeatedDefaultVariant { variable }, start)),
+        }
+    }
+}
+
+/// Joins the pieces of a pattern, removing the indentation common to its
+/// lines and the whitespace at its end.
+fn dedent(pieces: Vec<Piece>) -> Vec<AstNode> {
+    let common = pieces
+        .iter()
+        .filter_map(|piece| match piece {
+            Piece::Break { indent, .. } => Some(*indent),
+            _ => None,
+        })
+        .min()
+        .unwrap_or(0);
+    let mut nodes: Vec<AstNode> = Vec::new();
+    for (i, piece) in pieces.into_iter().enumerate() {
+        let (text, span) = match piece {
+            Piece::Node(node) => {
+                nodes.push(node);
+                continue;
+            }
+            Piece::Text(text, span) => (text, span),
+            // A pattern that starts on the line after the `=` keeps only
+            // the extra indentation of its first line.
+            Piece::Break { indent, span, .. } if i == 0 => (" ".repeat(indent - common), span),
+            Piece::Break {
+                newlines,
+                indent,
+                span,
+            } => ("\n".repeat(newlines) + &" ".repeat(indent - common), span),
+        };
+        match nodes.last_mut() {
+            Some(AstNode::Text(previous, previous_span)) => {
+                previous.push_str(&text);
+                previous_span.end = span.end;
+            }
+            _ => nodes.push(AstNode::Text(text, span)),
+        }
+    }
+    if let Some(AstNode::Text(text, span)) = nodes.last_mut() {
+        let trimmed = text.trim_end().len();
+        span.end -= text.len() - trimmed;
+        text.truncate(trimmed);
+        if text.is_empty() {
+            nodes.pop();
+        }
+    }
+    nodes.retain(|node| !matches!(node, AstNode::Text(text, _) if text.is_empty()));
+    nodes
+}
+
+/// The nodes of an inline expression in a placeable.
+fn inline(expression: Expression, span: Span) -> Vec<AstNode> {
+    let node = match expression {
+        Expression::String(text) | Expression::Number(text) => AstNode::Text(text, span),
+        Expression::Variable(variable) => AstNode::Variable(variable, span),
+        Expression::Reference(id) => AstNode::Text(format!("{{{}}}", id), span),
+        Expression::Placeable(nodes) => return nodes,
+        Expression::Function {
+            name,
+            positional,
+            named,
+        } => {
+            let Some(Expression::Variable(variable)) = positional.into_iter().next() else {
+                return vec![AstNode::Text(format!("{{{}()}}", name), span)];
+            };
+            let style = (!named.is_empty()).then(|| {
+                named
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            });
+            match name.as_str() {
+                "NUMBER" => AstNode::Number {
+                    variable,
+                    style,
+                    span,
+                },
+                "DATETIME" => AstNode::Date {
+                    variable,
+                    style,
+                    span,
+                },
+                _ => AstNode::Variable(variable, span),
+            }
+        }
+    };
+    vec![node]
+}
+
+/// The nodes of a select expression. Variants on a number whose keys are
+/// plural categories or numbers give a `plural` (or, for `type: "ordinal"`,
+/// a `selectordinal`), with numbers as exact `=N` matches; other variants on
+/// a variable give a `select`. The default variant is also the `other`
+/// option.
+fn select(selector: Expression, variants: Vec<Variant>, span: Span) -> Vec<AstNode> {
+    let Some((variable, ordinal)) = selector.variable() else {
+        return variants
+            .into_iter()
+            .find(|(_, default, _)| *default)
+            .map(|(_, _, nodes)| nodes)
+            .unwrap_or_default();
+    };
+    let variable = variable.to_string();
+    let is_number = |key: &str| key.parse::<f64>().is_ok();
+    let plural = variants.iter().all(|(key, _, _)| {
+        is_number(key)
+            || PluralCategory::ALL
+                .iter()
+                .any(|category| category.as_str() == key)
+    });
+    let mut options = Options::new();
+    let mut fallback = None;
+    for (key, default, nodes) in variants {
+        if default {
+            fallback = Some(nodes.clone());
+        }
+        let key = if plural && is_number(&key) {
+            format!("={}", key)
+        } else {
+            key
+        };
+        options.insert(key, nodes);
+    }
+    if let Some(nodes) = fallback {
+        options.entry("other".to_string()).or_insert(nodes);
+    }
+    vec![match (plural, ordinal) {
+        (true, false) => AstNode::Plural {
+            variable,
+            offset: 0,
+            options,
+            span,
+        },
+        (true, true) => AstNode::SelectOrdinal {
+            variable,
+            options,
+            span,
+        },
+        (false, _) => AstNode::Select {
+            variable,
+            options,
+            span,
+        },
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::print::Printer;
+
+    /// The pattern as an ICU message, with its errors.
+    fn icu(pattern: &str) -> (String, Vec<ParseErrorKind>) {
+        let (ast, errors) = parse_pattern(pattern);
+        let kinds = errors.into_iter().map(|error| error.kind).collect();
+        (Printer::new().print(&ast), kinds)
+    }
+
+    fn assert_icu(pattern: &str, expected: &str) {
+        assert_eq!(
+            icu(pattern),
+            (expected.to_string(), Vec::new()),
+            "{:?}",
+            pattern
+        );
+    }
+
+    const RESOURCE: &str = "\
+## Section comments belong to no entry.
+
+-brand = Firefox
+    .gender = masculine
+# Shown on the start page.
+welcome = Welcome to { -brand }, { $name }!
+login = Log in
+    .title = Sign in to { -brand }
+";
+
+    #[test]
+    fn reads_terms_attributes_and_comments() {
+        let (entries, errors) = parse_resource(RESOURCE);
+        assert!(errors.is_empty(), "{:?}", errors);
+        let keys: Vec<_> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            ["-brand", "-brand.gender", "welcome", "login", "login.title"]
+        );
+        assert!(entries[0].is_term() && !entries[2].is_term());
+        assert_eq!(entries[0].comment, None);
+        assert_eq!(
+            entries[2].comment.as_deref(),
+            Some("Shown on the start page.")
+        );
+
+        let title = &entries[4];
+        assert_eq!(&RESOURCE[title.key_span.start..title.key_span.end], "title");
+        assert_eq!(title.source, "Sign in to { -brand }");
+        assert_eq!(&RESOURCE[title.span.start..title.span.end], title.source);
+    }
+
+    #[test]
+    fn keeps_references_as_text() {
+        assert_icu(
+            "Welcome to { -brand }, { $name }!",
+            "Welcome to '{'-brand'}', {name}!",
+        );
+        assert_icu("See { help-link }", "See '{'help-link'}'");
+        assert_icu("{ \"{\" } and { 42 }", "'{' and 42");
+    }
+
+    #[test]
+    fn converts_function_calls() {
+        assert_icu(
+            "{ NUMBER($amount, minimumFractionDigits: 2) } on { DATETIME($day) }",
+            "{amount, number, minimumFractionDigits: 2} on {day, date}",
+        );
+    }
+
+    #[test]
+    fn converts_select_expressions() {
+        assert_icu(
+            "{ $count ->\n    [one] One email\n   *[other] { $count } emails\n}",
+            "{count, plural, one {One email} other {{count} emails}}",
+        );
+        assert_icu(
+            "{ $count ->\n    [0] None\n   *[other] Some\n}",
+            "{count, plural, =0 {None} other {Some}}",
+        );
+        assert_icu(
+            "{ NUMBER($pos, type: \"ordinal\") ->\n    [one] {$pos}st\n   *[other] {$pos}th\n}",
+            "{pos, selectordinal, one {{pos}st} other {{pos}th}}",
+        );
+        // Any other key makes it a select, and the default variant is also
+        // its `other`.
+        assert_icu(
+            "{ $gender ->\n   *[male] He\n    [female] She\n}",
+            "{gender, select, male {He} female {She} other {He}}",
+        );
+    }
+
+    #[test]
+    fn dedents_multiline_patterns() {
+        assert_icu(
+            "\n    First line\n      indented\n    second line",
+            "First line\n  indented\nsecond line",
+        );
+        assert_icu("Text\r\n    continued\r\n", "Text\ncontinued");
+    }
+
+    #[test]
+    fn reports_broken_select_expressions() {
+        let (_, errors) = icu("{ $n ->\n    [one] x\n}");
+        assert_eq!(
+            errors,
+            [ParseErrorKind::MissingDefaultVariant {
+                variable: "$n".to_string()
+            }]
+        );
+        let (_, errors) = icu("{ $n ->\n   *[a] x\n   *[b] y\n}");
+        assert_eq!(
+            errors,
+            [ParseErrorKind::RepeatedDefaultVariant {
+                variable: "$n".to_string()
+            }]
+        );
+        assert_eq!(icu("{ \"abc }").1, [ParseErrorKind::UnclosedString]);
+        assert_eq!(icu("Hi { $name").1, [ParseErrorKind::UnclosedArgument]);
+    }
+
+    #[test]
+    fn skips_junk_and_reads_on() {
+        let input = "first = One\nthis is junk\n  and more\nsecond = Two\n";
+        let (entries, errors) = parse_resource(input);
+        let keys: Vec<_> = entries.iter().map(|entry| entry.key.as_str()).collect();
+        assert_eq!(keys, ["first", "second"]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ParseErrorKind::InvalidEntry);
+        assert_eq!(
+            &input[errors[0].span.start..errors[0].span.end],
+            "this is junk\n  and more"
+        );
+    }
+}
@@ -1,75 +1,150 @@
+use indexmap::IndexMap;
 use serde_json::Value;
-use std::collections::HashMap;
-use std::fs::{self, File};
+use std::fs::File;
 use std::io::Write;
 use std::path::Path;
-use walkdir::WalkDir;
 
+use crate::arguments::{arguments, with_declared, Argument, ArgumentKind};
+use crate::catalog::{self, find_catalogs, normalize_locale, CatalogPath, CatalogPattern};
 use crate::parse::{AstNode, Parser};
+use crate::Dialect;
 
 pub struct TypeScriptGenerator {
-    translations: HashMap<String, String>,
+    /// Messages by namespace, then key; catalogs without a namespace are
+    /// under `None`.
+    translations: IndexMap<Option<String>, IndexMap<String, Entry>>,
+    dialect: Dialect,
+    pattern: Option<CatalogPattern>,
+    source_locale: String,
+}
+
+/// A message to write a type for.
+struct Entry {
+    message: String,
+    dialect: Dialect,
+    /// Arguments the catalog declares, such as typed ARB placeholders.
+    placeholders: Vec<Argument>,
+    /// Whether the message is in the source locale.
+    is_source: bool,
 }
 
 impl TypeScriptGenerator {
     pub fn new() -> Self {
+        Self::with_dialect(Dialect::default())
+    }
+
+    pub fn with_dialect(dialect: Dialect) -> Self {
         TypeScriptGenerator {
-            translations: HashMap::new(),
+            translations: IndexMap::new(),
+            dialect,
+            pattern: None,
+            source_locale: "en".to_string(),
         }
     }
 
-    pub fn process_directory(&mut self, dir_path: &str) -> std::io::Result<()> {
-        for entry in WalkDir::new(dir_path).into_iter().filter_map(|e| e.ok()) {
-            let path = entry.path();
-            if path.is_file() && path.extension().map_or(false, |ext| ext == "json") {
-                self.process_file(path)?;
-            }
+    /// Reads only the catalogs that match `pattern` in `process_directory`.
+    pub fn with_pattern(dialect: Dialect, pattern: CatalogPattern) -> Self {
+        TypeScriptGenerator {
+            pattern: Some(pattern),
+            ..Self::with_dialect(dialect)
         }
-        Ok(())
     }
 
-    fn process_file(&mut self, file_path: &Path) -> std::io::Result<()> {
-        let content = fs::read_to_string(file_path)?;
-        let json: Value = serde_json::from_str(&content)?;
-        self.extract_translations(&json, String::new());
+    /// The locale keys are typed from, `en` unless set; a key it lacks is
+    /// typed from the first other locale that has it.
+    pub fn set_source_locale(&mut self, locale: &str) {
+        self.source_locale = normalize_locale(locale);
+    }
+
+    pub fn process_directory(&mut self, dir_path: &str) -> std::io::Result<()> {
+        for (path, mut catalog) in find_catalogs(Path::new(dir_path), self.pattern.as_ref()) {
+            self.process_file(&path, &mut catalog)?;
+        }
         Ok(())
     }
 
-    fn extract_translations(&mut self, value: &Value, prefix: String) {
-        match value {
-            Value::Object(map) => {
-                for (k, v) in map {
-                    let new_key = if prefix.is_empty() {
-                        k.clone()
-                    } else {
-                        format!("{}.{}", prefix, k)
-                    };
-                    self.extract_translations(v, new_key);
+    fn process_file(&mut self, file_path: &Path, catalog: &mut CatalogPath) -> std::io::Result<()> {
+        let messages = catalog::read_messages(file_path, catalog)?;
+        let dialect = catalog::resolve_dialect(
+            file_path,
+            self.dialect,
+            messages.iter().filter_map(|message| message.value.as_str()),
+        );
+        let keys = self
+            .translations
+            .entry(catalog.namespace.clone())
+            .or_default();
+        for message in messages {
+            let Value::String(value) = message.value else {
+                continue;
+            };
+            let locale = message.locale.as_ref().or(catalog.locale.as_ref());
+            let entry = Entry {
+                message: value,
+                dialect,
+                placeholders: message.placeholders,
+                is_source: locale.is_none_or(|locale| *locale == self.source_locale),
+            };
+            let Some(previous) = keys.get_mut(&message.key) else {
+                keys.insert(message.key, entry);
+                continue;
+            };
+            // ARB metadata is usually only in the template catalog, which
+            // need not be the one the key is typed from.
+            if entry.is_source && !previous.is_source {
+                let placeholders = std::mem::take(&mut previous.placeholders);
+                *previous = entry;
+                if previous.placeholders.is_empty() {
+                    previous.placeholders = placeholders;
                 }
+            } else if previous.placeholders.is_empty() {
+                previous.placeholders = entry.placeholders;
             }
-            Value::String(s) => {
-                self.translations.insert(prefix, s.clone());
-            }
-            _ => {}
         }
+        Ok(())
     }
 
+    /// Writes a `Translations` type with one function per key. Keys of a
+    /// namespace are grouped under it; keys without one are at the top.
     pub fn generate_typescript_definitions(&self, output_path: &str) -> std::io::Result<()> {
         let mut file = File::create(output_path)?;
         writeln!(file, "export type Translations = {{")?;
 
-        for (key, value) in &self.translations {
-            let parser = Parser::new(value);
-            if let Ok(ast) = parser.parse() {
-                let params = extract_params(&ast);
-                let param_string = self.format_params(&params);
-                writeln!(file, "  \"{}\": (params: {}) => string,", key, param_string)?;
+        if let Some(keys) = self.translations.get(&None) {
+            self.write_keys(&mut file, keys, "  ")?;
+        }
+        for (namespace, keys) in &self.translations {
+            if let Some(namespace) = namespace {
+                writeln!(file, "  \"{}\": {{", namespace)?;
+                self.write_keys(&mut file, keys, "    ")?;
+                writeln!(file, "  }},")?;
             }
         }
 
         writeln!(file, "}}")
     }
 
+    fn write_keys(
+        &self,
+        file: &mut File,
+        keys: &IndexMap<String, Entry>,
+        indent: &str,
+    ) -> std::io::Result<()> {
+        for (key, entry) in keys {
+            let parser = Parser::with_dialect(&entry.message, entry.dialect);
+            if let Ok(ast) = parser.parse() {
+                let params = extract_params(&ast, &entry.placeholders);
+                let param_string = self.format_params(&params);
+                writeln!(
+                    file,
+                    "{}\"{}\": (params: {}) => string,",
+                    indent, key, param_string
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     fn format_params(&self, params: &[(String, String)]) -> String {
         let param_strings: Vec<String> = params
             .iter()
@@ -90,34 +165,76 @@ impl Default for TypeScriptGenerator {
     }
 }
 
-fn extract_params(node: &AstNode) -> Vec<(String, String)> {
-    let mut params = Vec::new();
-    match node {
-        AstNode::Root(children) => {
-            for child in children {
-                params.extend(extract_params(child));
-            }
-        }
-        AstNode::Variable(var) => {
-            params.push((var.clone(), "string".to_string()));
-        }
-        AstNode::Plural { variable, .. } => {
-            params.push((variable.clone(), "number".to_string()));
-        }
-        AstNode::Select { variable, options } => {
-            let option_types = options
-                .keys()
-                .map(|s| format!("\"{}\"", s))
-                .collect::<Vec<_>>()
-                .join(" | ");
-            params.push((variable.clone(), option_types));
-        }
-        AstNode::HtmlTag { children, .. } => {
-            for child in children {
-                params.extend(extract_params(child));
-            }
+fn extract_params(node: &AstNode, placeholders: &[Argument]) -> Vec<(String, String)> {
+    with_declared(arguments(node), placeholders)
+        .into_iter()
+        .map(|argument| {
+            let typ = param_type(&argument.kind);
+            (argument.name, typ)
+        })
+        .collect()
+}
+
+fn param_type(kind: &ArgumentKind) -> String {
+    match kind {
+        ArgumentKind::Select(options) => options
+            .iter()
+            .map(|option| format!("\"{}\"", option))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        kind if kind.is_number() => "number".to_string(),
+        kind if kind.is_date() => "Date | number".to_string(),
+        kind if kind.is_list() => "string[]".to_string(),
+        _ => "string".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// The `Translations` type generated for `files` written to a
+    /// temporary directory.
+    fn generate(name: &str, source_locale: &str, files: &[(&str, &str)]) -> String {
+        let dir = std::env::temp_dir().join(format!("typed-key-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for (path, content) in files {
+            fs::write(dir.join(path), content).unwrap();
         }
-        _ => {}
+        let mut generator = TypeScriptGenerator::new();
+        generator.set_source_locale(source_locale);
+        generator.process_directory(dir.to_str().unwrap()).unwrap();
+        let output = dir.join("types.d.ts");
+        generator
+            .generate_typescript_definitions(output.to_str().unwrap())
+            .unwrap();
+        let types = fs::read_to_string(&output).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        types
+    }
+
+    #[test]
+    fn types_keys_from_the_source_locale() {
+        let files = [
+            (
+                "en.json",
+                r#"{"k": "Hello {name}, {count, plural, one {# item} other {# items}}"}"#,
+            ),
+            ("fr.json", r#"{"k": "Bonjour", "only": "Seulement {x}"}"#),
+        ];
+        assert_eq!(
+            generate("source", "en", &files),
+            "export type Translations = {\n  \
+             \"k\": (params: { name: string, count: number }) => string,\n  \
+             \"only\": (params: { x: string }) => string,\n}\n"
+        );
+        assert_eq!(
+            generate("french-source", "fr", &files),
+            "export type Translations = {\n  \
+             \"k\": (params: {}) => string,\n  \
+             \"only\": (params: { x: string }) => string,\n}\n"
+        );
     }
-    params
 }
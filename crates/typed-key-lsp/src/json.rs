@@ -0,0 +1,348 @@
+//! JSON catalogs read with the position of every key and value.
+//!
+//! `serde_json` forgets where things are in the file; this parser keeps the
+//! byte span of each object key and value, so a message can be traced back
+//! to its place in the catalog, e.g. to jump to it or edit it.
+
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+use crate::catalog::Location;
+use crate::error::{JsonError, JsonErrorKind};
+use crate::span::Span;
+
+/// How deep objects and arrays may nest, so a runaway file cannot overflow
+/// the stack.
+pub const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonDocument {
+    pub value: Value,
+    /// Where each value but the root is, by JSON pointer: `/home/title`,
+    /// `/list/0`. Array items have the key of their array.
+    pub locations: HashMap<String, Location>,
+}
+
+impl JsonDocument {
+    pub fn location(&self, pointer: &str) -> Option<Location> {
+        self.locations.get(pointer).copied()
+    }
+}
+
+/// Parses a JSON document as strictly as `serde_json` does.
+pub fn parse(input: &str) -> Result<JsonDocument, JsonError> {
+    let mut parser = JsonParser {
+        input,
+        position: 0,
+        depth: 0,
+        locations: HashMap::new(),
+    };
+    let value = parser.value("", Span::default())?;
+    parser.skip_whitespace();
+    if parser.position < input.len() {
+        return Err(parser.unexpected("the end of the file"));
+    }
+    Ok(JsonDocument {
+        value,
+        locations: parser.locations,
+    })
+}
+
+/// `segment` escaped for a JSON pointer: `~` is `~0` and `/` is `~1`.
+pub fn pointer_segment(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+struct JsonParser<'a> {
+    input: &'a str,
+    position: usize,
+    /// Objects and arrays open around the current position.
+    depth: usize,
+    locations: HashMap<String, Location>,
+}
+
+impl JsonParser<'_> {
+    fn current(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.current(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.position += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.current() == Some(byte) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// An error at the current character, or at the end of the file.
+    fn unexpected(&self, expected: &'static str) -> JsonError {
+        let length = self.input[self.position..]
+            .chars()
+            .next()
+            .map_or(0, char::len_utf8);
+        JsonError::new(
+            JsonErrorKind::Unexpected { expected },
+            Span::new(self.position, self.position + length),
+        )
+    }
+
+    /// The value at `pointer`, recorded with the span of the key it is
+    /// under.
+    fn value(&mut self, pointer: &str, key: Span) -> Result<Value, JsonError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let value = match self.current() {
+            Some(b'{') => self.nested(|parser| parser.object(pointer))?,
+            Some(b'[') => self.nested(|parser| parser.array(pointer, key))?,
+            Some(b'"') => Value::String(self.string()?.0),
+            Some(b'-' | b'0'..=b'9') => Value::Number(self.number()?),
+            Some(b't') => self.keyword("true", Value::Bool(true))?,
+            Some(b'f') => self.keyword("false", Value::Bool(false))?,
+            Some(b'n') => self.keyword("null", Value::Null)?,
+            _ => return Err(self.unexpected("a value")),
+        };
+        if !pointer.is_empty() {
+            self.locations.insert(
+                pointer.to_string(),
+                Location {
+                    key,
+                    value: Span::new(start, self.position),
+                },
+            );
+        }
+        Ok(value)
+    }
+
+    /// Parses an object or array one level deeper, or fails at its opening
+    /// bracket past `MAX_DEPTH`.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Value, JsonError>,
+    ) -> Result<Value, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(JsonError::new(
+                JsonErrorKind::TooDeep { limit: MAX_DEPTH },
+                Span::new(self.position, self.position + 1),
+            ));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self, pointer: &str) -> Result<Value, JsonError> {
+        self.position += 1;
+        let mut map = Map::new();
+        if self.eat(b'}') {
+            return Ok(Value::Object(map));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.current() != Some(b'"') {
+                return Err(self.unexpected("a key in quotes"));
+            }
+            let (key, key_span) = self.string()?;
+            if !self.eat(b':') {
+                return Err(self.unexpected("`:`"));
+            }
+            let child = format!("{}/{}", pointer, pointer_segment(&key));
+            let value = self.value(&child, key_span)?;
+            map.insert(key, value);
+            if self.eat(b'}') {
+                return Ok(Value::Object(map));
+            }
+            if !self.eat(b',') {
+                return Err(self.unexpected("`,` or `}`"));
+            }
+        }
+    }
+
+    fn array(&mut self, pointer: &str, key: Span) -> Result<Value, JsonError> {
+        self.position += 1;
+        let mut items = Vec::new();
+        if self.eat(b']') {
+            return Ok(Value::Array(items));
+        }
+        loop {
+            let child = format!("{}/{}", pointer, items.len());
+            items.push(self.value(&child, key)?);
+            if self.eat(b']') {
+                return Ok(Value::Array(items));
+            }
+            if !self.eat(b',') {
+                return Err(self.unexpected("`,` or `]`"));
+            }
+        }
+    }
+
+    /// A quoted string and its span, quotes included. Escapes are decoded
+    /// by `serde_json`.
+    fn string(&mut self) -> Result<(String, Span), JsonError> {
+        let start = self.position;
+        self.position += 1;
+        loop {
+            match self.current() {
+                None => {
+                    return Err(JsonError::new(
+                        JsonErrorKind::UnterminatedString,
+                        Span::new(start, self.input.len()),
+                    ))
+                }
+                Some(b'"') => break,
+                Some(b'\\') => self.position += 2,
+                Some(_) => self.position += 1,
+            }
+        }
+        self.position += 1;
+        let span = Span::new(start, self.position);
+        let source = &self.input[span.start..span.end];
+        match serde_json::from_str(source) {
+            Ok(string) => Ok((string, span)),
+            Err(_) => Err(JsonError::new(
+                JsonErrorKind::InvalidString {
+                    string: source.to_string(),
+                },
+                span,
+            )),
+        }
+    }
+
+    fn number(&mut self) -> Result<Number, JsonError> {
+        let start = self.position;
+        while matches!(
+            self.current(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.position += 1;
+        }
+        let source = &self.input[start..self.position];
+        serde_json::from_str(source).map_err(|_| {
+            JsonError::new(
+                JsonErrorKind::InvalidNumber {
+                    number: source.to_string(),
+                },
+                Span::new(start, self.position),
+            )
+        })
+    }
+
+    fn keyword(&mut self, keyword: &'static str, value: Value) -> Result<Value, JsonError> {
+        if !self.input[self.position..].starts_with(keyword) {
+            return Err(self.unexpected("a value"));
+        }
+        self.position += keyword.len();
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The source text at the key and value spans of `pointer`.
+    fn located<'a>(input: &'a str, document: &JsonDocument, pointer: &str) -> (&'a str, &'a str) {
+        let location = document.location(pointer).unwrap();
+        (
+            &input[location.key.start..location.key.end],
+            &input[location.value.start..location.value.end],
+        )
+    }
+
+    #[test]
+    fn nested_objects() {
+        let input = r#"{"home": {"title": "Welcome", "nav": {"back": "Back"}}}"#;
+        let document = parse(input).unwrap();
+        assert_eq!(
+            located(input, &document, "/home"),
+            (
+                r#""home""#,
+                r#"{"title": "Welcome", "nav": {"back": "Back"}}"#
+            )
+        );
+        assert_eq!(
+            located(input, &document, "/home/title"),
+            (r#""title""#, r#""Welcome""#)
+        );
+        assert_eq!(
+            located(input, &document, "/home/nav/back"),
+            (r#""back""#, r#""Back""#)
+        );
+        assert_eq!(document.location(""), None);
+    }
+
+    #[test]
+    fn array_items_have_the_key_of_their_array() {
+        let input = "{\"days\": [\"Mon\",\n  \"Tue\", [1, true]]}";
+        let document = parse(input).unwrap();
+        assert_eq!(
+            located(input, &document, "/days"),
+            (r#""days""#, "[\"Mon\",\n  \"Tue\", [1, true]]")
+        );
+        assert_eq!(
+            located(input, &document, "/days/0"),
+            (r#""days""#, r#""Mon""#)
+        );
+        assert_eq!(
+            located(input, &document, "/days/1"),
+            (r#""days""#, r#""Tue""#)
+        );
+        assert_eq!(
+            located(input, &document, "/days/2/1"),
+            (r#""days""#, "true")
+        );
+    }
+
+    #[test]
+    fn escaped_keys() {
+        let input = r#"{"a/b": "slash", "c~d": "tilde", "say \"hi\"": "quote"}"#;
+        let document = parse(input).unwrap();
+        assert_eq!(
+            located(input, &document, "/a~1b"),
+            (r#""a/b""#, r#""slash""#)
+        );
+        assert_eq!(
+            located(input, &document, "/c~0d"),
+            (r#""c~d""#, r#""tilde""#)
+        );
+        assert_eq!(
+            located(input, &document, "/say \"hi\""),
+            (r#""say \"hi\"""#, r#""quote""#)
+        );
+    }
+
+    #[test]
+    fn multi_byte_text() {
+        let input = r#"{"café": "naïve 🎉", "next": "ok"}"#;
+        let document = parse(input).unwrap();
+        let location = document.location("/café").unwrap();
+        assert_eq!(location.key, Span::new(1, 8));
+        assert_eq!(location.value, Span::new(10, 23));
+        assert_eq!(located(input, &document, "/next"), (r#""next""#, r#""ok""#));
+        assert_eq!(document.value["café"], "naïve 🎉");
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let deep = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&deep).is_ok());
+
+        let input = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        let error = parse(&input).unwrap_err();
+        assert_eq!(error.kind, JsonErrorKind::TooDeep { limit: MAX_DEPTH });
+        assert_eq!(error.span, Span::new(MAX_DEPTH, MAX_DEPTH + 1));
+
+        let input = r#"{"a": "#.repeat(MAX_DEPTH + 1);
+        let error = parse(&input).unwrap_err();
+        assert_eq!(error.kind, JsonErrorKind::TooDeep { limit: MAX_DEPTH });
+    }
+}
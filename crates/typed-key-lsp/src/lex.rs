@@ -1,112 +1,482 @@
+use std::borrow::Cow;
+
+use crate::dialect::Dialect;
+use crate::span::Span;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub span: Span,
+}
+
+/// A token of a message. Everything but text borrows from the input; text is
+/// only copied when ICU quotes have to be undone.
 #[derive(Debug, PartialEq, Clone)]
-pub enum Token {
-    Variable(String),
-    Plural(String),
-    Select(String),
-    HtmlTag(String),
-    Text(String),
+pub enum TokenKind<'a> {
+    Text(Cow<'a, str>),
+    Pound,
+    /// An opening, closing or self-closing tag, e.g. `<a href="/terms">`.
+    HtmlTag(&'a str),
+    /// i18next `{{...}}` interpolation, braces included. The closing `}}` is
+    /// missing if the interpolation runs to the end of the input.
+    Interpolation(&'a str),
+    /// The `{` that opens an argument.
+    ArgumentStart,
+    /// The `}` that closes an argument.
+    ArgumentEnd,
+    /// A `,` between the name, type and style or options of an argument.
+    Comma,
+    /// An argument name, type keyword, option key or `offset:N`, trimmed.
+    Word(&'a str),
+    /// The style of a `number`, `date` or `time` argument, e.g.
+    /// `::currency/EUR`, trimmed.
+    Style(&'a str),
+    /// The `{` that opens an option message.
+    OptionStart,
+    /// The `}` that closes an option message.
+    OptionEnd,
 }
 
+/// What the lexer is in the middle of.
+#[derive(Debug, Clone, Copy)]
+enum Mode<'a> {
+    /// An option message. In `plural` and `selectordinal` options `#` stands
+    /// for the (offset-adjusted) number.
+    Message { in_plural: bool },
+    /// The name and type of an argument, before the second comma.
+    Argument {
+        commas: usize,
+        kind: Option<&'a str>,
+    },
+    /// The style of a simple argument, after the second comma.
+    Style,
+    /// The `key {message}` options of a choice argument.
+    Options { in_plural: bool },
+}
+
+/// Lexes a message in one pass, arguments and option messages included.
+/// Tokens that don't close by the end of the input simply stop.
 pub struct Lexer<'a> {
-    #[allow(dead_code)]
     input: &'a str,
-    chars: std::str::Chars<'a>,
-    current_char: Option<char>,
+    /// Byte offset of the next character in `input`.
+    position: usize,
+    /// Arguments and options the lexer is inside of, innermost last. Empty at
+    /// the top level of the message.
+    modes: Vec<Mode<'a>>,
+    dialect: Dialect,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
-        let mut chars = input.chars();
-        let current_char = chars.next();
         Lexer {
             input,
-            chars,
-            current_char,
+            position: 0,
+            modes: Vec::new(),
+            dialect: Dialect::Icu,
+        }
+    }
+
+    /// Lexes `input` in the given dialect. `Dialect::Auto` is resolved by
+    /// looking at `input` alone.
+    pub fn with_dialect(input: &'a str, dialect: Dialect) -> Self {
+        Lexer {
+            dialect: dialect.resolve([input]),
+            ..Lexer::new(input)
         }
     }
 
+    fn current(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn peek(&self) -> Option<char> {
+        let mut chars = self.input[self.position..].chars();
+        chars.next();
+        chars.next()
+    }
+
     fn advance(&mut self) {
-        self.current_char = self.chars.next();
+        if let Some(c) = self.current() {
+            self.position += c.len_utf8();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.input[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
     }
 
-    fn next_token(&mut self) -> Option<Token> {
-        match self.current_char {
-            Some('{') => self.lex_complex_token(),
-            Some('<') => self.lex_html_tag(),
-            Some(_) => self.lex_text(),
-            None => None,
+    fn token(&self, kind: TokenKind<'a>, start: usize) -> Token<'a> {
+        Token {
+            kind,
+            span: Span::new(start, self.position),
         }
     }
 
-    fn lex_complex_token(&mut self) -> Option<Token> {
-        let mut depth = 1;
-        let mut content = String::new();
-        content.push(self.current_char?);
+    /// A token of trimmed source text, e.g. a `Word`.
+    fn slice(&self, make: fn(&'a str) -> TokenKind<'a>, start: usize) -> Token<'a> {
+        let slice = self.input[start..self.position].trim_end();
+        Token {
+            kind: make(slice),
+            span: Span::new(start, start + slice.len()),
+        }
+    }
+
+    /// A single-character token.
+    fn punct(&mut self, kind: TokenKind<'a>) -> Token<'a> {
+        let start = self.position;
         self.advance();
+        self.token(kind, start)
+    }
+
+    fn next_token(&mut self) -> Option<Token<'a>> {
+        match self.modes.last().copied() {
+            None => self.lex_message(false),
+            Some(Mode::Message { in_plural }) => self.lex_message(in_plural),
+            Some(Mode::Argument { commas, kind }) => self.lex_argument(commas, kind),
+            Some(Mode::Style) => self.lex_style(),
+            Some(Mode::Options { in_plural }) => self.lex_options(in_plural),
+        }
+    }
 
-        while depth > 0 {
-            match self.current_char {
-                Some('{') => {
-                    depth += 1;
-                    content.push('{');
+    fn lex_message(&mut self, in_plural: bool) -> Option<Token<'a>> {
+        match self.current()? {
+            '{' if self.dialect == Dialect::I18next => {
+                if self.peek() == Some('{') {
+                    Some(self.lex_interpolation())
+                } else {
+                    Some(self.lex_text(in_plural))
                 }
-                Some('}') => {
-                    depth -= 1;
-                    content.push('}');
+            }
+            '{' => {
+                self.modes.push(Mode::Argument {
+                    commas: 0,
+                    kind: None,
+                });
+                Some(self.punct(TokenKind::ArgumentStart))
+            }
+            '}' if !self.modes.is_empty() => {
+                self.modes.pop();
+                Some(self.punct(TokenKind::OptionEnd))
+            }
+            '<' => match self.tag_end() {
+                Some(end) => {
+                    let start = self.position;
+                    self.position = end;
+                    Some(self.token(TokenKind::HtmlTag(&self.input[start..end]), start))
                 }
-                Some(c) => content.push(c),
-                None => break,
+                None => Some(self.lex_text(in_plural)),
+            },
+            '#' if in_plural => Some(self.punct(TokenKind::Pound)),
+            _ => Some(self.lex_text(in_plural)),
+        }
+    }
+
+    fn lex_argument(&mut self, commas: usize, kind: Option<&'a str>) -> Option<Token<'a>> {
+        self.skip_whitespace();
+        match self.current()? {
+            '}' => {
+                self.modes.pop();
+                Some(self.punct(TokenKind::ArgumentEnd))
+            }
+            ',' => {
+                let mode = self.modes.last_mut()?;
+                *mode = match (commas, kind) {
+                    (0, _) => Mode::Argument { commas: 1, kind },
+                    (_, Some("plural" | "selectordinal")) => Mode::Options { in_plural: true },
+                    (_, Some("select")) => Mode::Options { in_plural: false },
+                    _ => Mode::Style,
+                };
+                Some(self.punct(TokenKind::Comma))
+            }
+            _ => {
+                // Braces in a name, as in `{{x}}`, stay balanced.
+                let start = self.position;
+                let mut depth = 0usize;
+                while let Some(c) = self.current() {
+                    match c {
+                        ',' | '}' if depth == 0 => break,
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    self.advance();
+                }
+                let token = self.slice(TokenKind::Word, start);
+                if let (1, Some(Mode::Argument { kind, .. })) = (commas, self.modes.last_mut()) {
+                    *kind = Some(&self.input[start..token.span.end]);
+                }
+                Some(token)
+            }
+        }
+    }
+
+    /// Reads a style up to the `}` that closes its argument, skipping quoted
+    /// literals and balanced braces.
+    fn lex_style(&mut self) -> Option<Token<'a>> {
+        self.skip_whitespace();
+        if self.current()? == '}' {
+            self.modes.pop();
+            return Some(self.punct(TokenKind::ArgumentEnd));
+        }
+
+        let start = self.position;
+        let mut depth = 0;
+        while let Some(c) = self.current() {
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                '\'' => {
+                    self.advance();
+                    let rest = &self.input[self.position..];
+                    self.position += rest.find('\'').unwrap_or(rest.len());
+                }
+                _ => {}
             }
             self.advance();
         }
+        Some(self.slice(TokenKind::Style, start))
+    }
 
-        if content.contains("plural") {
-            Some(Token::Plural(content))
-        } else if content.contains("select") {
-            Some(Token::Select(content))
-        } else {
-            Some(Token::Variable(content))
+    fn lex_options(&mut self, in_plural: bool) -> Option<Token<'a>> {
+        self.skip_whitespace();
+        match self.current()? {
+            '{' => {
+                self.modes.push(Mode::Message { in_plural });
+                Some(self.punct(TokenKind::OptionStart))
+            }
+            '}' => {
+                self.modes.pop();
+                Some(self.punct(TokenKind::ArgumentEnd))
+            }
+            _ => {
+                let start = self.position;
+                let rest = &self.input[start..];
+                self.position += rest
+                    .find(|c: char| c.is_whitespace() || c == '{' || c == '}')
+                    .unwrap_or(rest.len());
+                Some(self.slice(TokenKind::Word, start))
+            }
         }
     }
 
-    fn lex_html_tag(&mut self) -> Option<Token> {
-        let mut content = String::new();
-        content.push(self.current_char?);
-        self.advance();
+    fn lex_interpolation(&mut self) -> Token<'a> {
+        let start = self.position;
+        let rest = &self.input[start + 2..];
+        self.position = match rest.find("}}") {
+            Some(end) => start + 2 + end + 2,
+            None => self.input.len(),
+        };
+        self.token(
+            TokenKind::Interpolation(&self.input[start..self.position]),
+            start,
+        )
+    }
 
-        while self.current_char != Some('>') && self.current_char.is_some() {
-            content.push(self.current_char.unwrap());
-            self.advance();
+    /// The end of the tag that starts at the current `<`, if it is one. A `<`
+    /// that isn't followed by a tag name, or whose tag isn't closed by a `>`
+    /// before the end of its message, is text.
+    fn tag_end(&self) -> Option<usize> {
+        if !self.peek().is_some_and(starts_tag) {
+            return None;
         }
 
-        content.push(self.current_char?); // Append the closing '>'
-        self.advance();
-        Some(Token::HtmlTag(content))
+        let nested = !self.modes.is_empty();
+        let mut quote = None;
+        let mut depth = 0usize;
+        for (index, c) in self.input[self.position..].char_indices() {
+            match quote {
+                Some(q) if c == q => quote = None,
+                Some(_) => {}
+                None if c == '"' || c == '\'' => quote = Some(c),
+                None if c == '>' => return Some(self.position + index + 1),
+                None if c == '{' => depth += 1,
+                None if c == '}' && depth == 0 && nested => return None,
+                None if c == '}' => depth = depth.saturating_sub(1),
+                None => {}
+            }
+        }
+        None
     }
 
-    fn lex_text(&mut self) -> Option<Token> {
-        let mut content = String::new();
+    /// Reads text up to the next syntax character. The text is borrowed from
+    /// the input unless it contains quotes that have to be undone.
+    fn lex_text(&mut self, in_plural: bool) -> Token<'a> {
+        let start = self.position;
+        let nested = !self.modes.is_empty();
+        let bytes = self.input.as_bytes();
+        let mut unquoted: Option<String> = None;
+        // Where the source not yet copied into `unquoted` starts.
+        let mut copied = start;
 
-        while let Some(c) = self.current_char {
-            if c == '{' || c == '<' {
-                break;
+        // Syntax characters are all ASCII, so the text can be scanned by byte.
+        while let Some(&b) = bytes.get(self.position) {
+            match b {
+                b'{' if self.dialect == Dialect::I18next
+                    && bytes.get(self.position + 1) == Some(&b'{') =>
+                {
+                    break
+                }
+                b'{' if self.dialect == Dialect::I18next => {}
+                b'{' => break,
+                b'}' if nested => break,
+                b'<' if self.tag_end().is_some() => break,
+                b'#' if in_plural => break,
+                b'\'' if self.dialect == Dialect::Icu => match self.peek() {
+                    Some('\'') => {
+                        unquoted
+                            .get_or_insert_with(String::new)
+                            .push_str(&self.input[copied..=self.position]);
+                        self.position += 2;
+                        copied = self.position;
+                        continue;
+                    }
+                    Some(next) if starts_quote(next, in_plural) => {
+                        let text = unquoted.get_or_insert_with(String::new);
+                        text.push_str(&self.input[copied..self.position]);
+                        self.position += 1;
+                        self.position += lex_quoted(&self.input[self.position..], text);
+                        copied = self.position;
+                        continue;
+                    }
+                    _ => {}
+                },
+                _ => {}
             }
-            content.push(c);
-            self.advance();
+            self.position += 1;
         }
 
-        if !content.is_empty() {
-            Some(Token::Text(content))
-        } else {
-            self.next_token() // Skip empty text and get the next token
+        let text = match unquoted {
+            Some(mut text) => {
+                text.push_str(&self.input[copied..self.position]);
+                Cow::Owned(text)
+            }
+            None => Cow::Borrowed(&self.input[start..self.position]),
+        };
+        self.token(TokenKind::Text(text), start)
+    }
+}
+
+/// Reads a quoted literal up to the closing apostrophe into `content` and
+/// returns how many bytes it took. `''` inside the literal stands for a
+/// single apostrophe; an unterminated literal runs to the end of the input.
+fn lex_quoted(input: &str, content: &mut String) -> usize {
+    let mut chars = input.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        if c == '\'' {
+            if chars.peek().map(|&(_, next)| next) != Some('\'') {
+                return index + 1;
+            }
+            chars.next();
         }
+        content.push(c);
     }
+    input.len()
+}
+
+/// Whether an apostrophe followed by `next` opens a quoted literal. `#` is
+/// only special inside plural option bodies.
+pub(crate) fn starts_quote(next: char, in_plural: bool) -> bool {
+    matches!(next, '{' | '}' | '<' | '>') || (next == '#' && in_plural)
+}
+
+/// Whether a `<` followed by `next` opens a tag, as in `<b>`, `<0>` or
+/// `</b>`. Any other `<` is text.
+fn starts_tag(next: char) -> bool {
+    next.is_alphanumeric() || next == '/'
 }
 
 impl<'a> Iterator for Lexer<'a> {
-    type Item = Token;
+    type Item = Token<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next_token()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    fn tokens(input: &str, dialect: Dialect) -> Vec<(usize, usize, TokenKind<'_>)> {
+        Lexer::with_dialect(input, dialect)
+            .map(|token| (token.span.start, token.span.end, token.kind))
+            .collect()
+    }
+
+    fn text(text: &str) -> TokenKind<'_> {
+        Text(Cow::Borrowed(text))
+    }
+
+    #[test]
+    fn undoes_icu_quotes() {
+        assert_eq!(tokens("It''s", Dialect::Icu), [(0, 5, text("It's"))]);
+        assert_eq!(tokens("It's", Dialect::Icu), [(0, 4, text("It's"))]);
+        assert_eq!(tokens("a '{' b", Dialect::Icu), [(0, 7, text("a { b"))]);
+    }
+
+    #[test]
+    fn unclosed_quote_runs_to_the_end() {
+        assert_eq!(
+            tokens("'{name} and more", Dialect::Icu),
+            [(0, 16, text("{name} and more"))]
+        );
+    }
+
+    #[test]
+    fn pound_is_only_special_in_plural_options() {
+        assert_eq!(
+            tokens("#1 {n, plural, other {# x}}", Dialect::Icu),
+            [
+                (0, 3, text("#1 ")),
+                (3, 4, ArgumentStart),
+                (4, 5, Word("n")),
+                (5, 6, Comma),
+                (7, 13, Word("plural")),
+                (13, 14, Comma),
+                (15, 20, Word("other")),
+                (21, 22, OptionStart),
+                (22, 23, Pound),
+                (23, 25, text(" x")),
+                (25, 26, OptionEnd),
+                (26, 27, ArgumentEnd),
+            ]
+        );
+        assert_eq!(
+            tokens("{n, plural, other {'#' #}}", Dialect::Icu)[6..9],
+            [(18, 19, OptionStart), (19, 23, text("# ")), (23, 24, Pound)]
+        );
+        assert_eq!(
+            tokens("{g, select, other {# x}}", Dialect::Icu)[6..9],
+            [
+                (18, 19, OptionStart),
+                (19, 22, text("# x")),
+                (22, 23, OptionEnd)
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_i18next_interpolations() {
+        assert_eq!(
+            tokens("Hi {{name}}!", Dialect::I18next),
+            [
+                (0, 3, text("Hi ")),
+                (3, 11, Interpolation("{{name}}")),
+                (11, 12, text("!")),
+            ]
+        );
+        assert_eq!(
+            tokens("{{- html}} x", Dialect::I18next),
+            [(0, 10, Interpolation("{{- html}}")), (10, 12, text(" x"))]
+        );
+        assert_eq!(
+            tokens("Hi {{name", Dialect::I18next),
+            [(0, 3, text("Hi ")), (3, 9, Interpolation("{{name"))]
+        );
+        // A single brace is text in i18next.
+        assert_eq!(tokens("{a}", Dialect::I18next), [(0, 3, text("{a}"))]);
+    }
+}
@@ -1,9 +1,45 @@
+pub mod dialect;
+pub use dialect::Dialect;
+
+pub mod span;
+pub use span::Span;
+
+pub mod error;
+pub use error::{JsonError, ParseError, PatternError, PoError, RenderError, SchemaError};
+
 pub mod lex;
 pub use lex::Lexer;
 
 pub mod parse;
 pub use parse::Parser;
 
+pub mod fluent;
+
+pub mod visit;
+pub use visit::{Fold, Visit};
+
+pub mod arguments;
+pub use arguments::Argument;
+
+pub mod print;
+pub use print::Printer;
+
+pub mod schema;
+
+pub mod plural;
+pub use plural::{PluralCategory, PluralRules};
+
+pub mod render;
+pub use render::Renderer;
+
+pub mod catalog;
+
+pub mod json;
+
+pub mod po;
+
+pub mod xliff;
+
 pub mod lsp;
 
 pub mod generate;
@@ -51,7 +51,7 @@ impl<'a> Visit<'a> for OptionsObjectVisitor {
 
 fn find_options_object(program: &Program, t_function_span: Span) -> (Option<Span>, bool, bool) {
     let mut visitor = OptionsObjectVisitor::new(t_function_span);
-    visitor.visit_program(&program);
+    visitor.visit_program(program);
     (
         visitor.options_object_span,
         visitor.has_second_argument,
@@ -121,7 +121,7 @@ fn create_insert_variable_edit(
 
     let t_visitor = TFunctionVisitor::new(Some(offset));
 
-    if let TFunctionInfo::InFunction(context) = t_visitor.analyze(&content, position) {
+    if let TFunctionInfo::InFunction(context) = t_visitor.analyze(content, position) {
         if let Some(t_function_span) = context.span {
             let (options_span, has_second_argument, is_empty_object) =
                 find_options_object(&program, t_function_span);
@@ -146,7 +146,7 @@ fn position_to_offset(position: &Position, source: &str) -> u32 {
         .take(position.line as usize)
         .map(|line| line.len() + 1)
         .sum::<usize>() as u32
-        + position.character as u32
+        + position.character
 }
 
 fn create_edit_from_span(
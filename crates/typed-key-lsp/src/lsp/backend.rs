@@ -69,6 +69,17 @@ impl LanguageServer for Backend {
         }
         Ok(None)
     }
+    async fn completion_resolve(&self, item: CompletionItem) -> Result<CompletionItem> {
+        let (sender, tx) = oneshot::channel();
+        let _ = self
+            .main_channel
+            .send(LspMessage::CompletionResolve(
+                Box::new(item.clone()),
+                sender,
+            ))
+            .await;
+        Ok(tx.await.unwrap_or(item))
+    }
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let (sender, tx) = oneshot::channel();
         let _ = self
@@ -1,5 +1,3 @@
-use std::collections::HashMap;
-
 use oxc::{
     ast::{
         ast::{CallExpression, Expression},
@@ -9,14 +7,14 @@ use oxc::{
 };
 use ropey::Rope;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use tokio::sync::mpsc::Receiver;
 use tower_lsp::{
     lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range, Url},
     Client,
 };
 
-use crate::{lsp::utils::traverse_ast_for_variables, Parser};
+use crate::arguments::arguments;
+use crate::lsp::store::TranslationStore;
 
 #[derive(Debug)]
 pub enum DiagnosticMessage {
@@ -43,15 +41,15 @@ pub fn diagnostics_task(client: Client, mut receiver: Receiver<DiagnosticMessage
 
 pub struct DiagnosticsVisitor<'a> {
     diagnostics: Vec<Diagnostic>,
-    translation_keys: &'a HashMap<String, Value>,
+    store: &'a TranslationStore,
     content: &'a Rope,
 }
 
 impl<'a> DiagnosticsVisitor<'a> {
-    pub fn new(translation_keys: &'a HashMap<String, Value>, content: &'a Rope) -> Self {
+    pub fn new(store: &'a TranslationStore, content: &'a Rope) -> Self {
         Self {
             diagnostics: Vec::new(),
-            translation_keys,
+            store,
             content,
         }
     }
@@ -64,7 +62,7 @@ impl<'a> DiagnosticsVisitor<'a> {
         }
     }
 
-    fn add_diagnostic(&mut self, key: &str, missing_var: &str, span: Span) {
+    fn add_diagnostic(&mut self, key: &str, missing_var: &str, locale: &str, span: Span) {
         let range = self.span_to_range(span);
         self.diagnostics.push(Diagnostic {
             range,
@@ -73,8 +71,8 @@ impl<'a> DiagnosticsVisitor<'a> {
             code_description: None,
             source: Some("typedkey".to_string()),
             message: format!(
-                "Missing required variable: {} for key: {}",
-                missing_var, key
+                "Missing required variable: {} for key: {} ({})",
+                missing_var, key, locale
             ),
             related_information: None,
             tags: None,
@@ -126,31 +124,40 @@ impl<'a> Visit<'a> for DiagnosticsVisitor<'a> {
                 if let Some(first_arg) = call_expr.arguments.first() {
                     if let Expression::StringLiteral(key_literal) = &first_arg.to_expression() {
                         let key = key_literal.value.to_string();
-                        if let Some(translation_value) = self.translation_keys.get(&key) {
-                            if let Some(translation_str) = translation_value.as_str() {
-                                let parser = Parser::new(translation_str);
-                                if let Ok(ast) = parser.parse() {
-                                    let mut required_vars = Vec::new();
-                                    traverse_ast_for_variables(&ast, &mut required_vars);
-
-                                    let provided_vars =
-                                        if let Some(second_arg) = call_expr.arguments.get(1) {
-                                            if let Expression::ObjectExpression(obj_expr) =
-                                                &second_arg.to_expression()
-                                            {
-                                                self.extract_provided_variables(obj_expr)
-                                            } else {
-                                                Vec::new()
-                                            }
-                                        } else {
-                                            Vec::new()
-                                        };
-
-                                    for var in required_vars {
-                                        if !provided_vars.contains(&var) {
-                                            self.add_diagnostic(&key, &var, call_expr.span);
-                                        }
-                                    }
+                        let provided_vars = if let Some(second_arg) = call_expr.arguments.get(1) {
+                            if let Expression::ObjectExpression(obj_expr) =
+                                &second_arg.to_expression()
+                            {
+                                self.extract_provided_variables(obj_expr)
+                            } else {
+                                Vec::new()
+                            }
+                        } else {
+                            Vec::new()
+                        };
+
+                        // Every locale's message must get its arguments; each
+                        // missing one is reported once, for the first locale
+                        // that uses it (the source locale if it does).
+                        let store = self.store;
+                        let mut reported = Vec::new();
+                        for translation in store.translations(&key) {
+                            if translation.as_str().is_none() {
+                                continue;
+                            }
+                            let Ok(ast) = translation.parse() else {
+                                continue;
+                            };
+                            for argument in arguments(&ast) {
+                                let var = argument.name;
+                                if !provided_vars.contains(&var) && !reported.contains(&var) {
+                                    self.add_diagnostic(
+                                        &key,
+                                        &var,
+                                        &translation.locale,
+                                        call_expr.span,
+                                    );
+                                    reported.push(var);
                                 }
                             }
                         }
@@ -161,10 +168,7 @@ impl<'a> Visit<'a> for DiagnosticsVisitor<'a> {
     }
 }
 
-pub fn generate_diagnostics(
-    content: &Rope,
-    translation_keys: &HashMap<String, Value>,
-) -> Vec<Diagnostic> {
+pub fn generate_diagnostics(content: &Rope, store: &TranslationStore) -> Vec<Diagnostic> {
     let allocator = oxc::allocator::Allocator::default();
     let source_type = oxc::span::SourceType::default()
         .with_typescript(true)
@@ -176,7 +180,7 @@ pub fn generate_diagnostics(
     let parse_result = oxc::parser::Parser::new(&allocator, &document_str, source_type).parse();
     let program = parse_result.program;
 
-    let mut visitor = DiagnosticsVisitor::new(translation_keys, content);
+    let mut visitor = DiagnosticsVisitor::new(store, content);
     visitor.visit_program(&program);
     visitor.diagnostics
 }
@@ -1,8 +1,8 @@
 use tokio::sync::{mpsc, oneshot};
 use tower_lsp::{
     lsp_types::{
-        CodeActionParams, CodeActionProviderCapability, CodeActionResponse, CompletionOptions,
-        CompletionParams, CompletionResponse, DidChangeConfigurationParams,
+        CodeActionParams, CodeActionProviderCapability, CodeActionResponse, CompletionItem,
+        CompletionOptions, CompletionParams, CompletionResponse, DidChangeConfigurationParams,
         DidChangeTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
         ExecuteCommandOptions, Hover, HoverParams, HoverProviderCapability, InitializeParams,
         InitializeResult, MessageType, OneOf, ServerCapabilities, ServerInfo,
@@ -14,7 +14,7 @@ use tower_lsp::{
 
 use crate::lsp::{
     action::handle_code_action,
-    completion::handle_completion,
+    completion::{handle_completion, resolve_completion},
     config::BackendConfig,
     fs::{find_workspace_package, TypedKeyTranslations},
     hover::hover,
@@ -33,6 +33,7 @@ pub enum LspMessage {
         CompletionParams,
         oneshot::Sender<Option<CompletionResponse>>,
     ),
+    CompletionResolve(Box<CompletionItem>, oneshot::Sender<CompletionItem>),
     Hover(HoverParams, oneshot::Sender<Option<Hover>>),
     DidChangeConfiguration(DidChangeConfigurationParams),
     CodeAction(
@@ -80,7 +81,7 @@ pub fn lsp_task(
                                 TextDocumentSyncKind::INCREMENTAL,
                             )),
                             completion_provider: Some(CompletionOptions {
-                                resolve_provider: Some(false),
+                                resolve_provider: Some(true),
                                 trigger_characters: Some(vec![
                                     "(".to_string(),
                                     ",".to_string(),
@@ -124,7 +125,24 @@ pub fn lsp_task(
                             ),
                         )
                         .await;
-                    let _ = lsp_data.load_translations();
+                    if let Err(error) = lsp_data.load_translations() {
+                        client
+                            .log_message(
+                                MessageType::ERROR,
+                                format!("Loading translations failed: {}", error),
+                            )
+                            .await;
+                    }
+                    client
+                        .log_message(
+                            MessageType::INFO,
+                            format!(
+                                "Loaded locales {:?}, source locale {}",
+                                lsp_data.store().locales().collect::<Vec<_>>(),
+                                lsp_data.store().source_locale()
+                            ),
+                        )
+                        .await;
                     let _ = sender.send(true);
                 }
                 LspMessage::DidChangeConfiguration(params) => {
@@ -140,8 +158,7 @@ pub fn lsp_task(
                 LspMessage::DidSave(params) => {
                     let uri = params.text_document.uri;
                     if let Some(rope) = lsp_data.documents.get(uri.as_str()) {
-                        let diagnostics =
-                            generate_diagnostics(&rope, lsp_data.get_translation_keys());
+                        let diagnostics = generate_diagnostics(rope, lsp_data.store());
                         let _ = diagnostics_channel
                             .send(DiagnosticMessage::Errors(uri, diagnostics))
                             .await;
@@ -150,18 +167,21 @@ pub fn lsp_task(
                 LspMessage::DidOpen(params) => {
                     let (sender, _) = oneshot::channel();
                     if let Some(package) = find_workspace_package(&params.text_document.uri) {
-                        lsp_data.config.translations_dir = package.join(&config.translations_dir);
+                        lsp_data.config = BackendConfig {
+                            translations_dir: package.join(&config.translations_dir),
+                            ..config.clone()
+                        };
                         let _ = lsp_channel.send(LspMessage::Initialized(sender)).await;
                     }
 
-                    let _ = lsp_data.did_open(params);
+                    lsp_data.did_open(params);
                 }
                 LspMessage::Completion(params, sender) => {
                     let mut completion_items = None;
                     let uri = params.text_document_position.text_document.uri.clone();
                     if let Some(rope) = lsp_data.documents.get(uri.as_str()) {
                         if let Ok(completion) =
-                            handle_completion(params, &rope, lsp_data.get_translation_keys()).await
+                            handle_completion(params, rope, lsp_data.store()).await
                         {
                             client
                                 .log_message(
@@ -174,6 +194,9 @@ pub fn lsp_task(
                         let _ = sender.send(completion_items);
                     };
                 }
+                LspMessage::CompletionResolve(item, sender) => {
+                    let _ = sender.send(resolve_completion(*item, lsp_data.store()));
+                }
                 LspMessage::Hover(params, sender) => {
                     let mut completion_items = None;
                     let uri = params
@@ -182,9 +205,7 @@ pub fn lsp_task(
                         .uri
                         .clone();
                     if let Some(rope) = lsp_data.documents.get(uri.as_str()) {
-                        if let Ok(completion) =
-                            hover(params, &rope, lsp_data.get_translation_keys()).await
-                        {
+                        if let Ok(completion) = hover(params, rope, lsp_data.store()).await {
                             completion_items = completion
                         }
                         let _ = sender.send(completion_items);
@@ -194,7 +215,7 @@ pub fn lsp_task(
                     let mut completion_items = None;
                     let uri = params.text_document.uri.clone();
                     if let Some(rope) = lsp_data.documents.get(uri.as_str()) {
-                        if let Ok(completion) = handle_code_action(params, &rope).await {
+                        if let Ok(completion) = handle_code_action(params, rope).await {
                             completion_items = completion
                         }
                         let _ = sender.send(completion_items);
@@ -1,20 +1,18 @@
-use std::collections::HashMap;
-
+use crate::arguments::{Argument, ArgumentKind};
 use crate::lsp::docs::TypedKeyDocs;
 use crate::lsp::visitor::{SecondParamInfo, TFunctionInfo, TFunctionVisitor};
-use crate::parse::AstNode;
-use crate::Parser;
 use ropey::Rope;
-use tower_lsp::jsonrpc::{Error, Result};
+use serde_json::json;
+use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
-use super::ast::extract_variables_and_options;
-use super::utils::{get_select_options, is_select_variable, traverse_ast_for_variables};
+use super::fs::Translation;
+use super::store::TranslationStore;
 
 pub async fn handle_completion(
     params: CompletionParams,
     document: &Rope,
-    translation_keys: &HashMap<String, serde_json::Value>,
+    store: &TranslationStore,
 ) -> Result<Option<CompletionResponse>> {
     let document_str = document.to_string();
 
@@ -32,77 +30,100 @@ pub async fn handle_completion(
                         return Ok(None);
                     }
 
-                    let Some(value) = translation_keys.get(translation_key) else {
+                    let Some(translation) = store.get(translation_key) else {
                         return Ok(None);
                     };
 
-                    let ast = Parser::new(value.as_str().unwrap_or_default())
-                        .parse()
-                        .map_err(|_| Error::internal_error())?;
-
-                    let completions = get_variable_completions(&ast, translation_key);
+                    let completions =
+                        get_variable_completions(&translation.arguments(), translation_key);
 
                     Ok(Some(CompletionResponse::Array(completions)))
                 }
                 SecondParamInfo::InObjectKey(_) => Ok(None),
                 SecondParamInfo::InObjectKeyValue(var_name) => {
-                    let Some(value) = translation_keys.get(translation_key) else {
+                    let Some(translation) = store.get(translation_key) else {
                         return Ok(None);
                     };
-                    let ast = Parser::new(value.as_str().unwrap_or_default())
-                        .parse()
-                        .map_err(|_| Error::internal_error())?;
-                    let completions = get_value_completions(&ast, &var_name, &translation_key);
+                    let completions =
+                        get_value_completions(&translation.arguments(), var_name, translation_key);
                     Ok(Some(CompletionResponse::Array(completions)))
                 }
             },
-            (Some(_), _) => provide_translation_key_completions(translation_keys),
+            (Some(translation_key), _) => {
+                provide_translation_key_completions(store, translation_key)
+            }
             (None, None) => Ok(None),
             (None, Some(_)) => Ok(None),
         },
     }
 }
 
+/// Offers the keys of the namespace `typed` starts with, or else the
+/// namespaces followed by every key. Key items only carry their key;
+/// `resolve_completion` adds their detail and documentation once the editor
+/// shows them.
 fn provide_translation_key_completions(
-    translation_keys: &HashMap<String, serde_json::Value>,
+    store: &TranslationStore,
+    typed: &str,
 ) -> Result<Option<CompletionResponse>> {
-    let completions = translation_keys
-        .iter()
-        .map(|(key, value)| {
-            let (variables, select_options) = extract_variables_and_options(value);
-
-            let detail = format_completion_detail(key, &variables, &select_options);
-            let documentation =
-                format_completion_documentation(key, value, &variables, &select_options);
-
-            CompletionItem {
-                label: key.to_owned(),
-                kind: Some(CompletionItemKind::CONSTANT),
-                detail: Some(detail),
-                documentation: Some(documentation),
-                ..Default::default()
-            }
-        })
-        .collect();
+    let (namespace, _) = store.split_key(typed);
+    let (namespaces, keys) = match namespace {
+        Some(namespace) => (Vec::new(), store.namespace_keys(namespace)),
+        None => (store.namespaces(), store.keys()),
+    };
+
+    let namespace_completions = namespaces.into_iter().map(|namespace| CompletionItem {
+        label: namespace.to_owned(),
+        kind: Some(CompletionItemKind::MODULE),
+        detail: Some(format!("Namespace: {}", namespace)),
+        sort_text: Some(format!("0{}", namespace)),
+        ..Default::default()
+    });
+    let key_completions = keys
+        .into_keys()
+        .enumerate()
+        .map(|(index, key)| CompletionItem {
+            data: Some(json!({ "key": key })),
+            label: key,
+            kind: Some(CompletionItemKind::CONSTANT),
+            sort_text: Some(format!("1{:06}", index)),
+            ..Default::default()
+        });
+    let completions = namespace_completions.chain(key_completions).collect();
 
     Ok(Some(CompletionResponse::Array(completions)))
 }
 
-fn get_variable_completions(ast: &AstNode, key: &str) -> Vec<CompletionItem> {
-    let variables = extract_variables_from_ast(ast);
-    variables
-        .into_iter()
-        .map(|var| {
-            let kind = if is_select_variable(ast, &var) {
+/// Fills in the detail and documentation of a translation key item.
+pub fn resolve_completion(mut item: CompletionItem, store: &TranslationStore) -> CompletionItem {
+    let key = item
+        .data
+        .as_ref()
+        .and_then(|data| data.get("key"))
+        .and_then(|key| key.as_str());
+    let Some((key, translation)) = key.and_then(|key| Some((key, store.get(key)?))) else {
+        return item;
+    };
+    let arguments = translation.arguments();
+    item.detail = Some(format_completion_detail(key, translation, &arguments));
+    item.documentation = Some(format_completion_documentation(key, store, &arguments));
+    item
+}
+
+fn get_variable_completions(arguments: &[Argument], key: &str) -> Vec<CompletionItem> {
+    arguments
+        .iter()
+        .map(|argument| {
+            let kind = if matches!(argument.kind, ArgumentKind::Select(_)) {
                 CompletionItemKind::ENUM
             } else {
                 CompletionItemKind::VARIABLE
             };
             CompletionItem {
-                label: var.clone(),
+                label: argument.name.clone(),
                 kind: Some(kind),
                 detail: Some(format!("Variable for key: {}", key)),
-                insert_text: Some(format!("{}: ", var)),
+                insert_text: Some(format!("{}: ", argument.name)),
                 insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
                 ..Default::default()
             }
@@ -110,10 +131,15 @@ fn get_variable_completions(ast: &AstNode, key: &str) -> Vec<CompletionItem> {
         .collect()
 }
 
-fn get_value_completions(ast: &AstNode, var_name: &str, key: &str) -> Vec<CompletionItem> {
-    if let Some(options) = get_select_options(ast, var_name) {
+fn get_value_completions(arguments: &[Argument], var_name: &str, key: &str) -> Vec<CompletionItem> {
+    let kind = arguments
+        .iter()
+        .find(|argument| argument.name == var_name)
+        .map(|argument| &argument.kind);
+    if let Some(ArgumentKind::Select(options)) = kind {
         options
-            .into_iter()
+            .iter()
+            .cloned()
             .map(|option| CompletionItem {
                 label: option.clone(),
                 kind: Some(CompletionItemKind::ENUM_MEMBER),
@@ -123,6 +149,24 @@ fn get_value_completions(ast: &AstNode, var_name: &str, key: &str) -> Vec<Comple
                 ..Default::default()
             })
             .collect()
+    } else if kind.is_some_and(ArgumentKind::is_number) {
+        vec![CompletionItem {
+            label: "1".to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(format!("Number for {}: {}", var_name, key)),
+            insert_text: Some("1".to_string()),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            ..Default::default()
+        }]
+    } else if kind.is_some_and(ArgumentKind::is_date) {
+        vec![CompletionItem {
+            label: "new Date()".to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            detail: Some(format!("Date for {}: {}", var_name, key)),
+            insert_text: Some("new Date()".to_string()),
+            insert_text_format: Some(InsertTextFormat::PLAIN_TEXT),
+            ..Default::default()
+        }]
     } else {
         vec![CompletionItem {
             label: "value".to_string(),
@@ -135,29 +179,14 @@ fn get_value_completions(ast: &AstNode, var_name: &str, key: &str) -> Vec<Comple
     }
 }
 
-fn extract_variables_from_ast(ast: &AstNode) -> Vec<String> {
-    let mut variables = Vec::new();
-    traverse_ast_for_variables(ast, &mut variables);
-    variables
-}
-
 fn format_completion_documentation(
     key: &str,
-    value: &serde_json::Value,
-    variables: &[String],
-    select_options: &HashMap<String, Vec<String>>,
+    store: &TranslationStore,
+    arguments: &[Argument],
 ) -> Documentation {
     let typed_key_docs = TypedKeyDocs::new();
-    let documentation = typed_key_docs.format_documentation(
-        key,
-        value,
-        variables,
-        &select_options
-            .values()
-            .flatten()
-            .cloned()
-            .collect::<Vec<_>>(),
-    );
+    let translations: Vec<_> = store.translations(key).collect();
+    let documentation = typed_key_docs.format_documentation(key, &translations, arguments);
 
     Documentation::MarkupContent(MarkupContent {
         kind: MarkupKind::Markdown,
@@ -167,17 +196,30 @@ fn format_completion_documentation(
 
 fn format_completion_detail(
     key: &str,
-    variables: &[String],
-    select_options: &HashMap<String, Vec<String>>,
+    translation: &Translation,
+    arguments: &[Argument],
 ) -> String {
-    let mut detail = format!("Translation key: {}", key);
-    if !variables.is_empty() {
+    let mut detail = format!("Translation key: {} ({})", key, translation.origin());
+    if !arguments.is_empty() {
         detail.push_str("\nParameters: ");
-        detail.push_str(&variables.join(", "));
+        detail.push_str(
+            &arguments
+                .iter()
+                .map(|argument| argument.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
     }
-    if !select_options.is_empty() {
+    let selects = arguments
+        .iter()
+        .filter_map(|argument| match &argument.kind {
+            ArgumentKind::Select(options) => Some((&argument.name, options)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if !selects.is_empty() {
         detail.push_str("\nSelect options:");
-        for (var, options) in select_options {
+        for (var, options) in selects {
             detail.push_str(&format!("\n  {}: {}", var, options.join(", ")));
         }
     }
@@ -1,9 +1,87 @@
 use std::path::PathBuf;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::Dialect;
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BackendConfig {
     pub translations_dir: PathBuf,
+    /// Where catalogs sit below `translations_dir`, e.g.
+    /// `{locale}/{namespace}.json`. Without it every file in a known
+    /// `CatalogFormat` is a catalog.
+    #[serde(default)]
+    pub translations_pattern: Option<String>,
+    #[serde(default)]
+    pub dialect: Dialect,
+    /// The locale the messages are written in, e.g. `en`. Hover and
+    /// diagnostics read it first; catalogs without a locale in their path
+    /// belong to it.
+    #[serde(default)]
+    pub source_locale: Option<String>,
+    #[serde(flatten)]
+    pub namespaces: Namespaces,
+}
+
+impl BackendConfig {
+    pub fn source_locale(&self) -> &str {
+        self.source_locale.as_deref().unwrap_or("en")
+    }
+}
+
+/// How keys name their i18next namespace, with i18next's option names:
+/// `t('checkout:summary.title')` reads `summary.title` from `checkout`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Namespaces {
+    /// Separates the namespace from the key; `false` turns prefixes off.
+    #[serde(
+        rename = "nsSeparator",
+        default = "default_ns_separator",
+        deserialize_with = "ns_separator"
+    )]
+    pub separator: Option<String>,
+    /// The namespace of keys without a prefix. Without one they are looked up
+    /// in every namespace.
+    #[serde(rename = "defaultNS", default)]
+    pub default: Option<String>,
+    /// Where keys without a prefix are looked up after `default`.
+    #[serde(rename = "fallbackNS", default, deserialize_with = "fallback_ns")]
+    pub fallback: Vec<String>,
+}
+
+impl Default for Namespaces {
+    fn default() -> Self {
+        Namespaces {
+            separator: default_ns_separator(),
+            default: None,
+            fallback: Vec::new(),
+        }
+    }
+}
+
+fn default_ns_separator() -> Option<String> {
+    Some(":".to_string())
+}
+
+/// A separator string, or `false` for none.
+fn ns_separator<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(separator)) if !separator.is_empty() => Some(separator),
+        Some(Value::Bool(false) | Value::String(_)) => None,
+        _ => default_ns_separator(),
+    })
+}
+
+/// A namespace, a list of them, or `false` for none.
+fn fallback_ns<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(namespace)) => vec![namespace],
+        Some(Value::Array(namespaces)) => namespaces
+            .into_iter()
+            .filter_map(|namespace| namespace.as_str().map(str::to_string))
+            .collect(),
+        _ => Vec::new(),
+    })
 }
@@ -1,6 +1,7 @@
-use serde_json::Value;
+use crate::arguments::{Argument, ArgumentKind};
+use crate::plural::check_categories;
 
-use crate::parse::{self, AstNode};
+use super::fs::Translation;
 
 pub struct TypedKeyDocs {}
 
@@ -9,54 +10,62 @@ impl TypedKeyDocs {
         Self {}
     }
 
+    /// Documents `key` with the arguments of its first translation, which is
+    /// shown first; the others follow under their locales.
     pub fn format_documentation(
         &self,
         key: &str,
-        value: &Value,
-        variables: &[String],
-        select_options: &[String],
+        translations: &[&Translation],
+        arguments: &[Argument],
     ) -> String {
         let mut doc = String::new();
 
-        // Parse the translation value to determine variable types
-        let (plural_vars, select_vars, simple_vars) = self.categorize_variables(value, variables);
-
         // Function signature
         doc.push_str("```typescript\n");
         doc.push_str(&format!("t(key: '{}', params?: {{", key));
 
         // Add variables with their types
-        for var in &plural_vars {
-            doc.push_str(&format!("\n  {}: number;", var));
-        }
-        for var in &select_vars {
-            let options = select_options.join(" | ");
-            doc.push_str(&format!("\n  {}: '{}';", var, options));
-        }
-        for var in &simple_vars {
-            doc.push_str(&format!("\n  {}: string | number;", var));
+        for argument in arguments {
+            doc.push_str(&format!(
+                "\n  {}: {};",
+                argument.name,
+                type_annotation(&argument.kind)
+            ));
         }
 
         doc.push_str("\n}): string\n```\n\n");
 
-        // Translation string
-        doc.push_str("**Translation:**\n");
-        doc.push_str("```i18n\n");
-        doc.push_str(value.as_str().unwrap_or_default());
-        doc.push_str("\n```\n\n");
+        // Translation strings, one per locale
+        for (index, translation) in translations.iter().enumerate() {
+            if index == 0 {
+                doc.push_str(&format!(
+                    "**Translation ({}):**\n",
+                    origin_link(translation)
+                ));
+            } else {
+                doc.push_str(&format!("**{}:**\n", origin_link(translation)));
+            }
+            doc.push_str("```i18n\n");
+            doc.push_str(translation.as_str().unwrap_or_default());
+            doc.push_str("\n```\n");
+            if let Some(note) = &translation.note {
+                doc.push_str(&format!("*{}*\n", note.replace('\n', "  \n")));
+            }
+            if let Ok(ast) = translation.parse() {
+                for error in check_categories(&ast, &translation.locale) {
+                    doc.push_str(&format!("> {}\n", error.kind));
+                }
+            }
+            doc.push('\n');
+        }
 
         // Parameters
-        if !variables.is_empty() {
+        if !arguments.is_empty() {
             doc.push_str("**Parameters:**\n");
-            for var in variables {
-                doc.push_str(&format!("- `{}`: ", var));
-                if plural_vars.contains(var) {
-                    doc.push_str("Number for plural form.\n");
-                } else if select_vars.contains(var) {
-                    doc.push_str(&format!("One of: {}.\n", select_options.join(", ")));
-                } else {
-                    doc.push_str("Value to interpolate.\n");
-                }
+            for argument in arguments {
+                doc.push_str(&format!("- `{}`: ", argument.name));
+                doc.push_str(&describe_argument(argument));
+                doc.push('\n');
             }
             doc.push('\n');
         }
@@ -64,118 +73,100 @@ impl TypedKeyDocs {
         // Example
         doc.push_str("**Example:**\n");
         doc.push_str("```typescript\n");
-        if variables.is_empty() {
+        if arguments.is_empty() {
             doc.push_str(&format!("t('{}');\n", key));
         } else {
-            doc.push_str(&format!("t('{}', {{ ", key));
-            for (i, var) in variables.iter().enumerate() {
-                if i > 0 {
-                    doc.push_str(", ");
-                }
-                if plural_vars.contains(var) {
-                    doc.push_str(&format!("{}: 1", var));
-                } else if select_vars.contains(var) {
-                    doc.push_str(&format!(
-                        "{}: '{}'",
-                        var,
-                        select_options.first().unwrap_or(&String::new())
-                    ));
-                } else {
-                    doc.push_str(&format!("{}: 'value'", var));
-                }
-            }
-            doc.push_str(" });\n");
+            let params = arguments
+                .iter()
+                .map(|argument| format!("{}: {}", argument.name, example_value(&argument.kind)))
+                .collect::<Vec<_>>()
+                .join(", ");
+            doc.push_str(&format!("t('{}', {{ {} }});\n", key, params));
         }
         doc.push_str("```\n");
 
         doc
     }
+}
 
-    fn categorize_variables(
-        &self,
-        value: &Value,
-        variables: &[String],
-    ) -> (Vec<String>, Vec<String>, Vec<String>) {
-        let mut plural_vars = Vec::new();
-        let mut select_vars = Vec::new();
-        let mut simple_vars = Vec::new();
-
-        if let Value::String(s) = value {
-            let parser = parse::Parser::new(s);
-            if let Ok(ast) = parser.parse() {
-                categorize_variables_from_ast(
-                    &ast,
-                    variables,
-                    &mut plural_vars,
-                    &mut select_vars,
-                    &mut simple_vars,
-                );
-            }
-        }
+fn type_annotation(kind: &ArgumentKind) -> String {
+    match kind {
+        ArgumentKind::Select(options) => options
+            .iter()
+            .map(|option| format!("'{}'", option))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        kind if kind.is_number() => "number".to_string(),
+        kind if kind.is_date() => "Date | number".to_string(),
+        kind if kind.is_list() => "string[]".to_string(),
+        _ => "string | number".to_string(),
+    }
+}
 
-        // Any variables not categorized are assumed to be simple
-        for var in variables {
-            if !plural_vars.contains(var)
-                && !select_vars.contains(var)
-                && !simple_vars.contains(var)
-            {
-                simple_vars.push(var.clone());
-            }
+fn describe_argument(argument: &Argument) -> String {
+    match &argument.kind {
+        ArgumentKind::Plural { offset: 0 } => "Number for plural form.".to_string(),
+        ArgumentKind::Plural { offset } => format!(
+            "Number for plural form (offset {}; `#` shows {} - {}).",
+            offset, argument.name, offset
+        ),
+        ArgumentKind::SelectOrdinal => "Number for ordinal form (1st, 2nd, 3rd...).".to_string(),
+        ArgumentKind::Select(options) => format!("One of: {}.", options.join(", ")),
+        ArgumentKind::Number(style) => describe_format("Number", style),
+        ArgumentKind::Date(style) => describe_format("Date", style),
+        ArgumentKind::Time(style) => describe_format("Time", style),
+        ArgumentKind::Formatted(formatters) => {
+            let chain = formatters
+                .iter()
+                .map(|formatter| match &formatter.options {
+                    Some(options) => format!("{}({})", formatter.name, options),
+                    None => formatter.name.clone(),
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Value formatted with `{}`.", chain)
         }
-
-        (plural_vars, select_vars, simple_vars)
+        ArgumentKind::Simple => "Value to interpolate.".to_string(),
     }
 }
 
-fn categorize_variables_from_ast(
-    node: &AstNode,
-    variables: &[String],
-    plural_vars: &mut Vec<String>,
-    select_vars: &mut Vec<String>,
-    simple_vars: &mut Vec<String>,
-) {
-    match node {
-        AstNode::Root(children) => {
-            for child in children {
-                categorize_variables_from_ast(
-                    child,
-                    variables,
-                    plural_vars,
-                    select_vars,
-                    simple_vars,
-                );
-            }
+fn example_value(kind: &ArgumentKind) -> String {
+    match kind {
+        ArgumentKind::Plural { .. } | ArgumentKind::SelectOrdinal => "1".to_string(),
+        ArgumentKind::Select(options) => {
+            format!(
+                "'{}'",
+                options.first().map(String::as_str).unwrap_or_default()
+            )
         }
-        AstNode::Plural { variable, .. } => {
-            if variables.contains(variable) && !plural_vars.contains(variable) {
-                plural_vars.push(variable.clone());
-            }
-        }
-        AstNode::Select { variable, .. } => {
-            if variables.contains(variable) && !select_vars.contains(variable) {
-                select_vars.push(variable.clone());
-            }
-        }
-        AstNode::Variable(var) => {
-            if variables.contains(var)
-                && !simple_vars.contains(var)
-                && !plural_vars.contains(var)
-                && !select_vars.contains(var)
-            {
-                simple_vars.push(var.clone());
-            }
-        }
-        AstNode::HtmlTag { children, .. } => {
-            for child in children {
-                categorize_variables_from_ast(
-                    child,
-                    variables,
-                    plural_vars,
-                    select_vars,
-                    simple_vars,
-                );
-            }
+        kind if kind.is_number() => "1234.5".to_string(),
+        kind if kind.is_date() => "new Date()".to_string(),
+        kind if kind.is_list() => "['value']".to_string(),
+        _ => "'value'".to_string(),
+    }
+}
+
+fn describe_format(kind: &str, style: &Option<String>) -> String {
+    match style {
+        Some(style) if style.starts_with("::") => {
+            format!("{} formatted with skeleton `{}`.", kind, style)
         }
-        _ => {}
+        Some(style) => format!("{} formatted as `{}`.", kind, style),
+        None => format!("{} formatted with the default style.", kind),
+    }
+}
+
+/// The origin of `translation`, linked to where it is in its catalog if that
+/// is known.
+fn origin_link(translation: &Translation) -> String {
+    match translation.location() {
+        Some(location) => format!(
+            "[{}]({}#L{},{})",
+            translation.origin(),
+            location.uri,
+            location.range.start.line + 1,
+            location.range.start.character + 1
+        ),
+        None => translation.origin(),
     }
 }
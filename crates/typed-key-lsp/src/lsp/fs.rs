@@ -3,17 +3,86 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::io;
-use std::path::{Path, PathBuf};
+use std::path::PathBuf;
 use tower_lsp::lsp_types::DidChangeTextDocumentParams;
 use tower_lsp::lsp_types::DidOpenTextDocumentParams;
 use tower_lsp::lsp_types::Url;
-use walkdir::WalkDir;
+use tower_lsp::lsp_types::{Location, Position, Range};
 
 use super::channels::lsp::LspMessage;
 use super::config::BackendConfig;
+use super::store::TranslationStore;
+use crate::arguments::{arguments, with_declared, Argument};
+use crate::catalog::{find_catalogs, parse_messages, resolve_dialect, CatalogPattern};
+use crate::parse::AstNode;
+use crate::span::Span;
+use crate::{Dialect, ParseError, Parser};
+
+/// A translation value together with the locale, namespace and dialect of
+/// the catalog it came from, and what the catalog says about it.
+#[derive(Debug, Clone)]
+pub struct Translation {
+    pub value: Value,
+    pub locale: String,
+    pub namespace: Option<String>,
+    pub dialect: Dialect,
+    pub note: Option<String>,
+    /// Arguments the catalog declares, such as typed ARB placeholders.
+    pub placeholders: Vec<Argument>,
+    /// The catalog the translation was read from.
+    pub file: PathBuf,
+    /// Where the key and the value are in `file`, for catalogs that keep
+    /// track of it; see `catalog::Message::location`.
+    pub key_range: Option<Range>,
+    pub value_range: Option<Range>,
+}
+
+impl Translation {
+    pub fn as_str(&self) -> Option<&str> {
+        self.value.as_str()
+    }
+
+    /// Where the translation comes from, for people: `fr`, or `fr/common`
+    /// in a namespaced layout.
+    pub fn origin(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}/{}", self.locale, namespace),
+            None => self.locale.clone(),
+        }
+    }
+
+    /// Where the translation is written, from its key to the end of its
+    /// value.
+    pub fn location(&self) -> Option<Location> {
+        let uri = Url::from_file_path(&self.file).ok()?;
+        let (key, value) = self.key_range.zip(self.value_range)?;
+        Some(Location::new(uri, Range::new(key.start, value.end)))
+    }
+
+    pub fn parse(&self) -> Result<AstNode, ParseError> {
+        self.parser().parse()
+    }
+
+    /// Best-effort parse for catalogs that are being edited; see
+    /// `Parser::parse_recovering`.
+    pub fn parse_recovering(&self) -> (AstNode, Vec<ParseError>) {
+        self.parser().parse_recovering()
+    }
+
+    /// The arguments the message expects, best effort while it is being
+    /// edited, with the kinds the catalog declares for them.
+    pub fn arguments(&self) -> Vec<Argument> {
+        let (ast, _) = self.parse_recovering();
+        with_declared(arguments(&ast), &self.placeholders)
+    }
+
+    fn parser(&self) -> Parser<'_> {
+        Parser::with_dialect(self.as_str().unwrap_or_default(), self.dialect)
+    }
+}
 
 pub struct TypedKeyTranslations {
-    translation_keys: HashMap<String, Value>,
+    store: TranslationStore,
     pub config: BackendConfig,
     main_channel: Option<std::sync::mpsc::Sender<LspMessage>>,
     pub documents: HashMap<String, Rope>,
@@ -23,7 +92,7 @@ pub struct TypedKeyTranslations {
 impl Clone for TypedKeyTranslations {
     fn clone(&self) -> Self {
         Self {
-            translation_keys: self.translation_keys.clone(),
+            store: self.store.clone(),
             config: self.config.clone(),
             main_channel: self.main_channel.clone(),
             documents: HashMap::new(),
@@ -35,7 +104,7 @@ impl Clone for TypedKeyTranslations {
 impl TypedKeyTranslations {
     pub fn default() -> Self {
         Self {
-            translation_keys: HashMap::new(),
+            store: TranslationStore::new(BackendConfig::default().source_locale()),
             config: BackendConfig::default(),
             main_channel: None,
             documents: HashMap::new(),
@@ -43,30 +112,65 @@ impl TypedKeyTranslations {
         }
     }
 
+    /// Loads every catalog in the translations directory, or only those that
+    /// match `translations_pattern` if it is set.
     pub fn load_translations(&mut self) -> io::Result<()> {
-        let translation_files: Vec<PathBuf> = WalkDir::new(&self.config.translations_dir)
-            .into_iter()
-            .filter_map(|entry| entry.ok())
-            .filter(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .map_or(false, |ext| ext.eq_ignore_ascii_case("json"))
-            })
-            .map(|entry| entry.into_path())
-            .collect();
+        let pattern = self
+            .config
+            .translations_pattern
+            .as_deref()
+            .map(CatalogPattern::new)
+            .transpose()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let translation_files = find_catalogs(&self.config.translations_dir, pattern.as_ref());
 
         if translation_files.is_empty() {
             return Ok(());
         }
 
-        self.translation_keys.clear(); // Clear existing keys before inserting new ones
+        self.store.clear(); // Clear existing keys before inserting new ones
+        self.store.set_source_locale(self.config.source_locale());
+        self.store.set_namespaces(self.config.namespaces.clone());
 
-        for file_path in translation_files {
-            match process_file(&file_path) {
-                Ok(keys) => {
-                    for (key, value) in keys {
-                        self.translation_keys.insert(key, value);
+        for (file_path, mut catalog) in translation_files {
+            let content = match fs::read_to_string(&file_path) {
+                Ok(content) => content,
+                Err(e) => {
+                    eprintln!("Error reading file {:?}: {}", file_path, e);
+                    continue;
+                }
+            };
+            let text = Rope::from_str(&content);
+            match parse_messages(&file_path, &content, &mut catalog) {
+                Ok(messages) => {
+                    let dialect = resolve_dialect(
+                        &file_path,
+                        self.config.dialect,
+                        messages.iter().filter_map(|message| message.value.as_str()),
+                    );
+                    for message in messages {
+                        let locale = message
+                            .locale
+                            .or_else(|| catalog.locale.clone())
+                            .unwrap_or_else(|| self.store.source_locale().to_string());
+                        self.store.insert(
+                            message.key,
+                            Translation {
+                                value: message.value,
+                                locale,
+                                namespace: catalog.namespace.clone(),
+                                dialect,
+                                note: message.note,
+                                placeholders: message.placeholders,
+                                file: file_path.clone(),
+                                key_range: message
+                                    .location
+                                    .map(|location| span_to_range(&text, location.key)),
+                                value_range: message
+                                    .location
+                                    .map(|location| span_to_range(&text, location.value)),
+                            },
+                        );
                     }
                 }
                 Err(e) => {
@@ -78,8 +182,8 @@ impl TypedKeyTranslations {
         Ok(())
     }
 
-    pub fn get_translation_keys(&self) -> &HashMap<String, Value> {
-        &self.translation_keys
+    pub fn store(&self) -> &TranslationStore {
+        &self.store
     }
 
     pub fn did_open(&mut self, params: DidOpenTextDocumentParams) {
@@ -114,43 +218,6 @@ impl TypedKeyTranslations {
     }
 }
 
-fn process_file(path: &Path) -> io::Result<Vec<(String, Value)>> {
-    let content = fs::read_to_string(path)?;
-    let json: Value = serde_json::from_str(&content).map_err(|e| {
-        io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("Error parsing JSON in file {:?}: {}", path, e),
-        )
-    })?;
-    let keys = extract_keys(&json, String::new());
-    Ok(keys)
-}
-
-fn extract_keys(value: &Value, prefix: String) -> Vec<(String, Value)> {
-    match value {
-        Value::Object(map) => map
-            .iter()
-            .flat_map(|(key, val)| {
-                let new_prefix = if prefix.is_empty() {
-                    key.clone()
-                } else {
-                    format!("{}.{}", prefix, key)
-                };
-                extract_keys(val, new_prefix)
-            })
-            .collect(),
-        Value::Array(arr) => arr
-            .iter()
-            .enumerate()
-            .flat_map(|(index, val)| {
-                let new_prefix = format!("{}[{}]", prefix, index);
-                extract_keys(val, new_prefix)
-            })
-            .collect(),
-        _ => vec![(prefix, value.clone())],
-    }
-}
-
 pub fn find_workspace_package(uri: &Url) -> Option<PathBuf> {
     let path = uri.to_file_path().ok()?;
     let mut current_dir = path.parent()?;
@@ -168,3 +235,36 @@ pub fn find_workspace_package(uri: &Url) -> Option<PathBuf> {
         }
     }
 }
+
+/// The range of the byte `span` in `text`, with columns counted in UTF-16
+/// code units, the position encoding LSP clients use by default.
+fn span_to_range(text: &Rope, span: Span) -> Range {
+    let position = |offset: usize| {
+        let char = text.byte_to_char(offset);
+        let line = text.char_to_line(char);
+        let column = text.char_to_utf16_cu(char) - text.char_to_utf16_cu(text.line_to_char(line));
+        Position::new(line as u32, column as u32)
+    };
+    Range::new(position(span.start), position(span.end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges_count_utf16_code_units() {
+        let content = "{\n  \"é\": {\n    \"naïve\": \"🎉 ok\"\n  }\n}";
+        let document = crate::json::parse(content).unwrap();
+        let location = document.location("/é/naïve").unwrap();
+        let text = Rope::from_str(content);
+        assert_eq!(
+            span_to_range(&text, location.key),
+            Range::new(Position::new(2, 4), Position::new(2, 11))
+        );
+        assert_eq!(
+            span_to_range(&text, location.value),
+            Range::new(Position::new(2, 13), Position::new(2, 20))
+        );
+    }
+}
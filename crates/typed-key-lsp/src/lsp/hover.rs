@@ -1,7 +1,5 @@
-use std::collections::HashMap;
-
-use super::ast::extract_variables_and_options;
 use super::docs::TypedKeyDocs;
+use super::store::TranslationStore;
 use super::visitor::{TFunctionInfo, TFunctionVisitor};
 use oxc::span::Span;
 use ropey::Rope;
@@ -11,7 +9,7 @@ use tower_lsp::lsp_types::*;
 pub(crate) async fn hover(
     params: HoverParams,
     document: &Rope,
-    translation_keys: &HashMap<String, serde_json::Value>,
+    store: &TranslationStore,
 ) -> Result<Option<Hover>> {
     let position = params.text_document_position_params.position;
     let document_str = document.to_string();
@@ -22,7 +20,7 @@ pub(crate) async fn hover(
         TFunctionInfo::NotInFunction => Ok(None),
         TFunctionInfo::InFunction(context) => match (&context.first_param, &context.second_param) {
             (Some(first_param), _) => {
-                provide_t_function_documentation(&first_param, context.span, translation_keys)
+                provide_t_function_documentation(first_param, context.span, store)
             }
             _ => Ok(None),
         },
@@ -32,16 +30,17 @@ pub(crate) async fn hover(
 fn provide_t_function_documentation(
     key: &str,
     span: Option<Span>,
-    translation_keys: &HashMap<String, serde_json::Value>,
+    store: &TranslationStore,
 ) -> Result<Option<Hover>> {
-    if let Some(value) = translation_keys.get(key) {
-        let (variables, select_options) = extract_variables_and_options(value);
-        let documentation = format_hover_documentation(key, value, &variables, &select_options);
+    if let Some(translation) = store.get(key) {
+        let translations: Vec<_> = store.translations(key).collect();
+        let documentation =
+            TypedKeyDocs::new().format_documentation(key, &translations, &translation.arguments());
 
         if let Some(span) = span {
             let range = Range {
-                start: Position::new(span.start as u32, span.start),
-                end: Position::new(span.end as u32, span.end as u32),
+                start: Position::new(span.start, span.start),
+                end: Position::new(span.end, span.end),
             };
             Ok(Some(Hover {
                 contents: HoverContents::Markup(MarkupContent {
@@ -63,22 +62,3 @@ fn provide_t_function_documentation(
         Ok(None)
     }
 }
-
-fn format_hover_documentation(
-    key: &str,
-    value: &serde_json::Value,
-    variables: &[String],
-    select_options: &std::collections::HashMap<String, Vec<String>>,
-) -> String {
-    let typed_key_docs = TypedKeyDocs::new();
-    typed_key_docs.format_documentation(
-        key,
-        value,
-        variables,
-        &select_options
-            .values()
-            .flatten()
-            .cloned()
-            .collect::<Vec<_>>(),
-    )
-}
@@ -1,5 +1,4 @@
 pub(crate) mod action;
-pub(crate) mod ast;
 pub mod backend;
 pub(crate) mod channels;
 pub(crate) mod completion;
@@ -7,5 +6,5 @@ pub(crate) mod config;
 pub(crate) mod docs;
 pub(crate) mod fs;
 pub(crate) mod hover;
-pub(crate) mod utils;
+pub(crate) mod store;
 pub(crate) mod visitor;
@@ -0,0 +1,350 @@
+use indexmap::{IndexMap, IndexSet};
+
+use super::config::Namespaces;
+use super::fs::Translation;
+use crate::catalog::normalize_locale;
+
+/// The keys of one locale by namespace; catalogs without a namespace are
+/// under `None`.
+type Catalogs = IndexMap<Option<String>, IndexMap<String, Translation>>;
+
+/// Translations by locale, then namespace, then key.
+///
+/// Lookups read the source locale first and fall back to the other locales in
+/// the order they were loaded, so a key that is only translated somewhere
+/// still resolves. Keys are written as i18next does: `checkout:title` names
+/// its namespace, and `title` is looked up in the namespaces `Namespaces`
+/// puts in scope.
+#[derive(Debug, Clone)]
+pub struct TranslationStore {
+    locales: IndexMap<String, Catalogs>,
+    source_locale: String,
+    namespaces: Namespaces,
+    /// Every namespace some catalog has, kept as catalogs are inserted so
+    /// `split_key` does not scan them per key.
+    loaded_namespaces: IndexSet<String>,
+}
+
+impl TranslationStore {
+    pub fn new(source_locale: &str) -> Self {
+        TranslationStore {
+            locales: IndexMap::new(),
+            source_locale: normalize_locale(source_locale),
+            namespaces: Namespaces::default(),
+            loaded_namespaces: IndexSet::new(),
+        }
+    }
+
+    pub fn source_locale(&self) -> &str {
+        &self.source_locale
+    }
+
+    pub fn set_source_locale(&mut self, locale: &str) {
+        self.source_locale = normalize_locale(locale);
+    }
+
+    pub fn set_namespaces(&mut self, namespaces: Namespaces) {
+        self.namespaces = namespaces;
+    }
+
+    pub fn clear(&mut self) {
+        self.locales.clear();
+        self.loaded_namespaces.clear();
+    }
+
+    /// Adds `translation` under its own locale and namespace; a later file
+    /// with the same locale, namespace and key replaces it.
+    pub fn insert(&mut self, key: String, translation: Translation) {
+        if let Some(namespace) = &translation.namespace {
+            self.loaded_namespaces.insert(namespace.clone());
+        }
+        self.locales
+            .entry(translation.locale.clone())
+            .or_default()
+            .entry(translation.namespace.clone())
+            .or_default()
+            .insert(key, translation);
+    }
+
+    /// The loaded locales, the source locale first.
+    pub fn locales(&self) -> impl Iterator<Item = &str> {
+        self.catalogs().map(|(locale, _)| locale.as_str())
+    }
+
+    /// The loaded namespaces, in the order they were first seen.
+    pub fn namespaces(&self) -> Vec<&str> {
+        self.loaded_namespaces.iter().map(String::as_str).collect()
+    }
+
+    /// Splits `checkout:title` into `checkout` and `title` if `checkout` is a
+    /// loaded namespace; any other key has no namespace.
+    pub fn split_key<'k>(&self, key: &'k str) -> (Option<&'k str>, &'k str) {
+        let split = self
+            .namespaces
+            .separator
+            .as_deref()
+            .and_then(|separator| key.split_once(separator))
+            .filter(|(namespace, _)| self.loaded_namespaces.contains(*namespace));
+        match split {
+            Some((namespace, key)) => (Some(namespace), key),
+            None => (None, key),
+        }
+    }
+
+    /// The translation of `key` in the source locale, or else in the first
+    /// locale that has it.
+    pub fn get(&self, key: &str) -> Option<&Translation> {
+        self.catalogs()
+            .find_map(|(_, catalogs)| self.lookup(catalogs, key))
+    }
+
+    /// Every translation of `key`, the source locale first.
+    pub fn translations<'s>(&'s self, key: &'s str) -> impl Iterator<Item = &'s Translation> {
+        self.catalogs()
+            .filter_map(move |(_, catalogs)| self.lookup(catalogs, key))
+    }
+
+    /// Every key as a `t()` call would write it, with the translation `get`
+    /// returns for it: the keys of the source locale in file order, then keys
+    /// only other locales have. Keys of namespaces out of scope carry their
+    /// namespace prefix.
+    pub fn keys(&self) -> IndexMap<String, &Translation> {
+        let mut keys = IndexMap::new();
+        for (_, catalogs) in self.catalogs() {
+            for (namespace, catalog) in catalogs {
+                let prefix = match (namespace, &self.namespaces.separator) {
+                    (Some(namespace), Some(separator)) if !self.in_scope(namespace) => {
+                        Some(format!("{}{}", namespace, separator))
+                    }
+                    _ => None,
+                };
+                for (key, translation) in catalog {
+                    let key = match &prefix {
+                        Some(prefix) => format!("{}{}", prefix, key),
+                        None => key.clone(),
+                    };
+                    keys.entry(key).or_insert(translation);
+                }
+            }
+        }
+        keys
+    }
+
+    /// The keys of `namespace`, written with its prefix.
+    pub fn namespace_keys(&self, namespace: &str) -> IndexMap<String, &Translation> {
+        let separator = self.namespaces.separator.as_deref().unwrap_or_default();
+        let mut keys = IndexMap::new();
+        for (_, catalogs) in self.catalogs() {
+            for (key, translation) in named(catalogs, namespace).into_iter().flatten() {
+                keys.entry(format!("{}{}{}", namespace, separator, key))
+                    .or_insert(translation);
+            }
+        }
+        keys
+    }
+
+    fn lookup<'s>(&'s self, catalogs: &'s Catalogs, key: &str) -> Option<&'s Translation> {
+        let (namespace, key) = self.split_key(key);
+        self.search_order(catalogs, namespace)
+            .into_iter()
+            .find_map(|keys| keys.get(key))
+    }
+
+    /// Whether a key without a prefix can come from `namespace`: the default
+    /// namespace and the fallbacks can, or all of them if there is no
+    /// default.
+    fn in_scope(&self, namespace: &str) -> bool {
+        match &self.namespaces.default {
+            Some(default) => {
+                default == namespace || self.namespaces.fallback.iter().any(|n| n == namespace)
+            }
+            None => true,
+        }
+    }
+
+    /// The catalogs of one locale a key is looked up in, in order: the named
+    /// namespace only, or else catalogs without a namespace, then the default
+    /// namespace and the fallbacks (or every namespace if there is no
+    /// default).
+    fn search_order<'s>(
+        &'s self,
+        catalogs: &'s Catalogs,
+        namespace: Option<&str>,
+    ) -> Vec<&'s IndexMap<String, Translation>> {
+        match (namespace, &self.namespaces.default) {
+            (Some(namespace), _) => named(catalogs, namespace).into_iter().collect(),
+            (None, Some(default)) => catalogs
+                .get(&None)
+                .into_iter()
+                .chain(named(catalogs, default))
+                .chain(
+                    self.namespaces
+                        .fallback
+                        .iter()
+                        .filter_map(|fallback| named(catalogs, fallback)),
+                )
+                .collect(),
+            (None, None) => {
+                let (plain, namespaced): (Vec<_>, Vec<_>) = catalogs
+                    .iter()
+                    .partition(|(namespace, _)| namespace.is_none());
+                plain
+                    .into_iter()
+                    .chain(namespaced)
+                    .map(|(_, catalog)| catalog)
+                    .collect()
+            }
+        }
+    }
+
+    fn catalogs(&self) -> impl Iterator<Item = (&String, &Catalogs)> {
+        let source = self.locales.get_key_value(&self.source_locale);
+        source.into_iter().chain(
+            self.locales
+                .iter()
+                .filter(|(locale, _)| **locale != self.source_locale),
+        )
+    }
+}
+
+fn named<'c>(catalogs: &'c Catalogs, namespace: &str) -> Option<&'c IndexMap<String, Translation>> {
+    catalogs
+        .iter()
+        .find(|(candidate, _)| candidate.as_deref() == Some(namespace))
+        .map(|(_, catalog)| catalog)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dialect;
+
+    fn translation(locale: &str, namespace: Option<&str>, value: &str) -> Translation {
+        Translation {
+            value: value.into(),
+            locale: locale.to_string(),
+            namespace: namespace.map(str::to_string),
+            dialect: Dialect::default(),
+            note: None,
+            placeholders: Vec::new(),
+            file: Default::default(),
+            key_range: None,
+            value_range: None,
+        }
+    }
+
+    /// A store with `entries` of `(locale, namespace, key, value)`.
+    fn store(
+        namespaces: Namespaces,
+        entries: &[(&str, Option<&str>, &str, &str)],
+    ) -> TranslationStore {
+        let mut store = TranslationStore::new("en");
+        store.set_namespaces(namespaces);
+        for (locale, namespace, key, value) in entries {
+            store.insert(key.to_string(), translation(locale, *namespace, value));
+        }
+        store
+    }
+
+    fn get<'s>(store: &'s TranslationStore, key: &str) -> Option<&'s str> {
+        store.get(key).and_then(Translation::as_str)
+    }
+
+    #[test]
+    fn splits_keys_of_loaded_namespaces() {
+        let entries = [("en", Some("checkout"), "title", "Checkout")];
+        let namespaced = store(Namespaces::default(), &entries);
+        assert_eq!(namespaced.namespaces(), ["checkout"]);
+        assert_eq!(
+            namespaced.split_key("checkout:title"),
+            (Some("checkout"), "title")
+        );
+        assert_eq!(
+            namespaced.split_key("checkout:a:b"),
+            (Some("checkout"), "a:b")
+        );
+        assert_eq!(
+            namespaced.split_key("missing:title"),
+            (None, "missing:title")
+        );
+        assert_eq!(namespaced.split_key("title"), (None, "title"));
+
+        let without_separator = Namespaces {
+            separator: None,
+            ..Namespaces::default()
+        };
+        let plain = store(without_separator, &entries);
+        assert_eq!(plain.split_key("checkout:title"), (None, "checkout:title"));
+    }
+
+    #[test]
+    fn keys_of_namespaces_not_loaded_are_plain_keys() {
+        let store = store(
+            Namespaces::default(),
+            &[
+                ("en", None, "time:short", "Short"),
+                ("en", Some("checkout"), "title", "Checkout"),
+            ],
+        );
+        assert_eq!(get(&store, "time:short"), Some("Short"));
+        assert_eq!(get(&store, "missing:title"), None);
+        assert_eq!(get(&store, "checkout:title"), Some("Checkout"));
+        // Without a default namespace every namespace is in scope.
+        assert_eq!(get(&store, "title"), Some("Checkout"));
+    }
+
+    #[test]
+    fn looks_up_default_then_fallback_namespaces() {
+        let namespaces = Namespaces {
+            default: Some("common".to_string()),
+            fallback: vec!["shared".to_string(), "legacy".to_string()],
+            ..Namespaces::default()
+        };
+        let store = store(
+            namespaces,
+            &[
+                ("en", Some("legacy"), "title", "Legacy"),
+                ("en", Some("legacy"), "old", "Old"),
+                ("en", Some("shared"), "title", "Shared"),
+                ("en", Some("shared"), "save", "Save"),
+                ("en", Some("common"), "title", "Common"),
+                ("en", Some("checkout"), "pay", "Pay"),
+            ],
+        );
+        assert_eq!(get(&store, "title"), Some("Common"));
+        assert_eq!(get(&store, "save"), Some("Save"));
+        assert_eq!(get(&store, "old"), Some("Old"));
+        assert_eq!(get(&store, "shared:title"), Some("Shared"));
+        assert_eq!(get(&store, "pay"), None);
+        assert_eq!(get(&store, "checkout:pay"), Some("Pay"));
+
+        let keys: Vec<_> = store.keys().into_keys().collect();
+        assert_eq!(keys, ["title", "old", "save", "checkout:pay"]);
+        let keys: Vec<_> = store.namespace_keys("shared").into_keys().collect();
+        assert_eq!(keys, ["shared:title", "shared:save"]);
+    }
+
+    #[test]
+    fn falls_back_to_other_locales() {
+        let mut store = store(
+            Namespaces::default(),
+            &[
+                ("fr", None, "title", "Titre"),
+                ("fr", None, "only", "Seulement"),
+                ("de", None, "only", "Nur"),
+                ("en", None, "title", "Title"),
+            ],
+        );
+        assert_eq!(get(&store, "title"), Some("Title"));
+        assert_eq!(get(&store, "only"), Some("Seulement"));
+        let titles: Vec<_> = store
+            .translations("title")
+            .filter_map(Translation::as_str)
+            .collect();
+        assert_eq!(titles, ["Title", "Titre"]);
+        assert_eq!(store.locales().collect::<Vec<_>>(), ["en", "fr", "de"]);
+
+        store.set_source_locale("de");
+        assert_eq!(get(&store, "only"), Some("Nur"));
+    }
+}
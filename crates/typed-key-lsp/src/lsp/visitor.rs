@@ -22,6 +22,7 @@ pub struct FunctionContext {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::enum_variant_names)]
 pub enum SecondParamInfo {
     InObject(String),
     InObjectKey(String),
@@ -58,7 +59,7 @@ impl<'a> TFunctionVisitor<'a> {
         let offset = self.position_to_offset(&position, source);
 
         let mut visitor = TFunctionVisitor::new(Some(offset));
-        (&mut visitor).visit_program(&program);
+        visitor.visit_program(&program);
         visitor.cursor_info.unwrap_or(TFunctionInfo::NotInFunction)
     }
 
@@ -95,9 +96,7 @@ impl<'a> TFunctionVisitor<'a> {
     }
 
     fn get_key_name(&self, key: &PropertyKey) -> Option<String> {
-        match key {
-            _ => Some(key.name().unwrap_or_default().into_owned()),
-        }
+        Some(key.name().unwrap_or_default().into_owned())
     }
 
     fn position_to_offset(&self, position: &Position, source: &str) -> u32 {
@@ -106,64 +105,60 @@ impl<'a> TFunctionVisitor<'a> {
             .take(position.line as usize)
             .map(|line| line.len() + 1)
             .sum::<usize>() as u32
-            + position.character as u32
+            + position.character
     }
 }
 
 impl<'a> Visit<'a> for TFunctionVisitor<'a> {
     fn enter_node(&mut self, kind: AstKind<'a>) {
         match kind {
-            AstKind::CallExpression(call_expr) => {
-                if self.is_t_function_call(call_expr) {
-                    let first_param = call_expr.arguments.get(0).and_then(|arg| {
-                        if let Expression::StringLiteral(lit) = &arg.to_expression() {
-                            Some(lit.value.to_string())
-                        } else {
-                            None
-                        }
-                    });
-                    self.enter_t_function(first_param, call_expr.span);
-
-                    if self.is_offset_in_span(call_expr.span) {
-                        self.cursor_info = self.current_context.last().cloned();
+            AstKind::CallExpression(call_expr) if self.is_t_function_call(call_expr) => {
+                let first_param = call_expr.arguments.first().and_then(|arg| {
+                    if let Expression::StringLiteral(lit) = &arg.to_expression() {
+                        Some(lit.value.to_string())
+                    } else {
+                        None
                     }
+                });
+                self.enter_t_function(first_param, call_expr.span);
+
+                if self.is_offset_in_span(call_expr.span) {
+                    self.cursor_info = self.current_context.last().cloned();
                 }
             }
-            AstKind::StringLiteral(string_literal) => {
-                if !self.current_context.is_empty() && self.is_offset_in_span(string_literal.span) {
-                    // Only update first_param if it's empty or None
-                    self.update_current_info(|context| {
-                        if context.first_param.as_ref().map_or(true, |s| s.is_empty()) {
-                            context.first_param = Some(string_literal.value.to_string());
-                        } else {
-                        }
-                    });
-                }
+            AstKind::StringLiteral(string_literal)
+                if !self.current_context.is_empty()
+                    && self.is_offset_in_span(string_literal.span) =>
+            {
+                // Only update first_param if it's empty or None
+                self.update_current_info(|context| {
+                    if context.first_param.as_ref().is_none_or(|s| s.is_empty()) {
+                        context.first_param = Some(string_literal.value.to_string());
+                    }
+                });
             }
-            AstKind::ObjectExpression(obj_expr) => {
-                if !self.current_context.is_empty() && self.is_offset_in_span(obj_expr.span) {
-                    self.update_current_info(|context| {
-                        context.second_param = Some(SecondParamInfo::InObject(
-                            context.first_param.clone().unwrap_or("".to_string()),
-                        ));
-                    });
-                }
+            AstKind::ObjectExpression(obj_expr)
+                if !self.current_context.is_empty() && self.is_offset_in_span(obj_expr.span) =>
+            {
+                self.update_current_info(|context| {
+                    context.second_param = Some(SecondParamInfo::InObject(
+                        context.first_param.clone().unwrap_or("".to_string()),
+                    ));
+                });
             }
-            AstKind::ObjectProperty(property) => {
-                if !self.current_context.is_empty() {
-                    if self.is_offset_in_span(property.key.span()) {
-                        if let Some(key_name) = self.get_key_name(&property.key) {
-                            self.update_current_info(|context| {
-                                context.second_param = Some(SecondParamInfo::InObjectKey(key_name));
-                            });
-                        }
-                    } else if self.is_offset_in_span(property.value.span()) {
-                        if let Some(key_name) = self.get_key_name(&property.key) {
-                            self.update_current_info(|context| {
-                                context.second_param =
-                                    Some(SecondParamInfo::InObjectKeyValue(key_name));
-                            });
-                        }
+            AstKind::ObjectProperty(property) if !self.current_context.is_empty() => {
+                if self.is_offset_in_span(property.key.span()) {
+                    if let Some(key_name) = self.get_key_name(&property.key) {
+                        self.update_current_info(|context| {
+                            context.second_param = Some(SecondParamInfo::InObjectKey(key_name));
+                        });
+                    }
+                } else if self.is_offset_in_span(property.value.span()) {
+                    if let Some(key_name) = self.get_key_name(&property.key) {
+                        self.update_current_info(|context| {
+                            context.second_param =
+                                Some(SecondParamInfo::InObjectKeyValue(key_name));
+                        });
                     }
                 }
             }
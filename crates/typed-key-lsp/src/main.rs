@@ -1,11 +1,18 @@
 use clap::{Parser, Subcommand};
+use indexmap::IndexMap;
 use miette::{IntoDiagnostic, Result, WrapErr};
 use std::fs;
 use std::path::PathBuf;
 use tower_lsp::{LspService, Server};
+use typed_key::catalog::{CatalogPath, CatalogPattern};
 use typed_key::generate::TypeScriptGenerator;
 use typed_key::lsp::backend::Backend;
-use typed_key::{Lexer as TypedKeyLexer, Parser as TypedKeyParser};
+use typed_key::parse::AstNode;
+use typed_key::plural::check_categories;
+use typed_key::render::{self, Tags, Value};
+use typed_key::{
+    catalog, schema, Dialect, Lexer as TypedKeyLexer, Parser as TypedKeyParser, Printer, Renderer,
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -18,15 +25,71 @@ struct Args {
 enum Commands {
     Tokenize {
         filename: PathBuf,
+        #[arg(long, value_enum, default_value_t)]
+        dialect: Dialect,
     },
     Parse {
         filename: PathBuf,
         #[arg(long)]
         json: bool,
+        /// Read a JSON catalog and print the AST of every message as one JSON
+        /// document, keyed by message key.
+        #[arg(long)]
+        catalog: bool,
+        /// Print a best-effort AST and report every error instead of stopping
+        /// at the first one.
+        #[arg(long)]
+        recover: bool,
+        /// Also check that plural and selectordinal arguments have an option
+        /// for every category this locale uses.
+        #[arg(long)]
+        locale: Option<String>,
+        #[arg(long, value_enum, default_value_t)]
+        dialect: Dialect,
+    },
+    /// Parse a message and print it back, e.g. to normalize it.
+    Print {
+        filename: PathBuf,
+        /// Read a JSON AST written by `parse --json` instead of a message.
+        #[arg(long)]
+        ast: bool,
+        /// Print the canonical form: options in CLDR order, one per line.
+        #[arg(long)]
+        pretty: bool,
+        #[arg(long, value_enum, default_value_t)]
+        dialect: Dialect,
+    },
+    /// Render a message from a JSON catalog with argument values.
+    Render {
+        catalog: PathBuf,
+        key: String,
+        /// Locale whose plural rules pick plural and selectordinal options.
+        #[arg(long, default_value = "en")]
+        locale: String,
+        /// An argument value, e.g. `--arg count=3` or `--arg user.name=Ann`.
+        /// Values are text; plural, selectordinal and number arguments read
+        /// them as numbers. Repeat for each argument.
+        #[arg(long = "arg", value_name = "NAME=VALUE", value_parser = parse_argument)]
+        args: Vec<(String, Value)>,
+        /// Write the content of tags without the tags themselves.
+        #[arg(long)]
+        strip_tags: bool,
+        #[arg(long, value_enum, default_value_t)]
+        dialect: Dialect,
     },
     GenerateTypes {
         input_dir: PathBuf,
         output_file: PathBuf,
+        /// Read only the catalogs matching this layout below `input_dir`, e.g.
+        /// `{locale}/{namespace}.json` or `messages_{locale}.json`.
+        #[arg(long)]
+        pattern: Option<String>,
+        /// Locale keys are typed from; a key it lacks is typed from the
+        /// first other locale that has it.
+        #[arg(long, default_value = "en")]
+        source_locale: String,
+        #[arg(long, value_enum, default_value_t)]
+        dialect: Dialect,
     },
 }
 
@@ -41,36 +104,89 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
     match args.command {
-        Some(Commands::Tokenize { filename }) => tokenize(filename),
-        Some(Commands::Parse { filename, json }) => parse_file(filename, json),
+        Some(Commands::Tokenize { filename, dialect }) => tokenize(filename, dialect),
+        Some(Commands::Parse {
+            filename,
+            json,
+            catalog,
+            recover,
+            locale,
+            dialect,
+        }) => {
+            if catalog {
+                parse_catalog(filename, recover, locale, dialect)
+            } else {
+                parse_file(filename, json, recover, locale, dialect)
+            }
+        }
+        Some(Commands::Print {
+            filename,
+            ast,
+            pretty,
+            dialect,
+        }) => print_file(filename, ast, pretty, dialect),
+        Some(Commands::Render {
+            catalog,
+            key,
+            locale,
+            args,
+            strip_tags,
+            dialect,
+        }) => render(catalog, key, locale, args, strip_tags, dialect),
         Some(Commands::GenerateTypes {
             input_dir,
             output_file,
-        }) => generate_types(input_dir, output_file),
+            pattern,
+            source_locale,
+            dialect,
+        }) => generate_types(input_dir, output_file, pattern, &source_locale, dialect),
         None => start_lsp().await,
     }
 }
 
-fn tokenize(filename: PathBuf) -> Result<()> {
+fn tokenize(filename: PathBuf, dialect: Dialect) -> Result<()> {
     let file_contents = fs::read_to_string(&filename)
         .into_diagnostic()
         .wrap_err_with(|| format!("reading '{}' failed", filename.display()))?;
-    let lexer = TypedKeyLexer::new(&file_contents);
+    let lexer = TypedKeyLexer::with_dialect(&file_contents, dialect);
     for token in lexer {
-        println!("{:?}", token);
+        println!("{}..{} {:?}", token.span.start, token.span.end, token.kind);
     }
     Ok(())
 }
 
-fn parse_file(filename: PathBuf, json: bool) -> Result<()> {
+fn parse_file(
+    filename: PathBuf,
+    json: bool,
+    recover: bool,
+    locale: Option<String>,
+    dialect: Dialect,
+) -> Result<()> {
     let file_contents = fs::read_to_string(&filename)
         .into_diagnostic()
         .wrap_err_with(|| format!("reading '{}' failed", filename.display()))?;
 
-    let parser = TypedKeyParser::new(&file_contents);
-    let parsed = parser
-        .parse()
-        .map_err(|e| miette::miette!("Parsing failed: {}", e))?;
+    let parser = TypedKeyParser::with_dialect(&file_contents, dialect);
+    let report = |error| {
+        miette::Report::new(error).with_source_code(miette::NamedSource::new(
+            filename.display().to_string(),
+            file_contents.clone(),
+        ))
+    };
+    let (parsed, mut errors) = if recover {
+        parser.parse_recovering()
+    } else {
+        (parser.parse().map_err(report)?, Vec::new())
+    };
+    if let Some(locale) = &locale {
+        errors.extend(check_categories(&parsed, locale));
+        if !recover && !errors.is_empty() {
+            return Err(report(errors.remove(0)));
+        }
+    }
+    for error in errors {
+        eprintln!("{:?}", report(error));
+    }
 
     if json {
         let json_output = parsed.to_json();
@@ -87,8 +203,142 @@ fn parse_file(filename: PathBuf, json: bool) -> Result<()> {
     Ok(())
 }
 
-fn generate_types(input_dir: PathBuf, output_file: PathBuf) -> Result<()> {
-    let mut generator = TypeScriptGenerator::new();
+fn parse_catalog(
+    filename: PathBuf,
+    recover: bool,
+    locale: Option<String>,
+    dialect: Dialect,
+) -> Result<()> {
+    let messages = catalog::read_catalog(&filename, &mut CatalogPath::default())
+        .into_diagnostic()
+        .wrap_err_with(|| format!("reading '{}' failed", filename.display()))?;
+    let dialect =
+        catalog::resolve_dialect(&filename, dialect, messages.values().map(String::as_str));
+
+    let mut parsed = IndexMap::new();
+    for (key, message) in &messages {
+        let report = |error| {
+            miette::Report::new(error)
+                .with_source_code(miette::NamedSource::new(key.clone(), message.clone()))
+        };
+        let parser = TypedKeyParser::with_dialect(message, dialect);
+        let (node, mut errors) = if recover {
+            parser.parse_recovering()
+        } else {
+            (parser.parse().map_err(report)?, Vec::new())
+        };
+        if let Some(locale) = &locale {
+            errors.extend(check_categories(&node, locale));
+            if !recover && !errors.is_empty() {
+                return Err(report(errors.remove(0)));
+            }
+        }
+        for error in errors {
+            eprintln!("{:?}", report(error));
+        }
+        parsed.insert(key.clone(), node);
+    }
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema::catalog_to_json(&parsed))
+            .into_diagnostic()
+            .wrap_err("Failed to serialize to JSON")?
+    );
+    Ok(())
+}
+
+fn print_file(filename: PathBuf, ast: bool, pretty: bool, dialect: Dialect) -> Result<()> {
+    let file_contents = fs::read_to_string(&filename)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("reading '{}' failed", filename.display()))?;
+
+    let parsed = if ast {
+        let document = serde_json::from_str(&file_contents)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("reading '{}' failed", filename.display()))?;
+        AstNode::from_json(&document)?
+    } else {
+        TypedKeyParser::with_dialect(&file_contents, dialect)
+            .parse()
+            .map_err(|e| {
+                miette::Report::new(e).with_source_code(miette::NamedSource::new(
+                    filename.display().to_string(),
+                    file_contents.clone(),
+                ))
+            })?
+    };
+
+    let printer = Printer::with_dialect(dialect);
+    if pretty {
+        print!("{}", printer.pretty(&parsed));
+    } else {
+        print!("{}", printer.print(&parsed));
+    }
+
+    Ok(())
+}
+
+fn render(
+    catalog: PathBuf,
+    key: String,
+    locale: String,
+    args: Vec<(String, Value)>,
+    strip_tags: bool,
+    dialect: Dialect,
+) -> Result<()> {
+    let messages = catalog::read_catalog(&catalog, &mut CatalogPath::default())
+        .into_diagnostic()
+        .wrap_err_with(|| format!("reading '{}' failed", catalog.display()))?;
+    let Some(message) = messages.get(&key) else {
+        miette::bail!("key `{}` not found in '{}'", key, catalog.display());
+    };
+
+    let source = || miette::NamedSource::new(key.clone(), message.clone());
+    let dialect =
+        catalog::resolve_dialect(&catalog, dialect, messages.values().map(String::as_str));
+    let parsed = TypedKeyParser::with_dialect(message, dialect)
+        .parse()
+        .map_err(|error| miette::Report::new(error).with_source_code(source()))?;
+
+    let tags = if strip_tags { Tags::Strip } else { Tags::Keep };
+    let mut arguments = IndexMap::new();
+    for (name, value) in args {
+        render::insert_argument(&mut arguments, &name, value);
+    }
+    let rendered = Renderer::with_tags(&locale, tags)
+        .render(&parsed, &arguments)
+        .map_err(|error| miette::Report::new(error).with_source_code(source()))?;
+    println!("{}", rendered);
+    Ok(())
+}
+
+/// Parses a `name=value` argument of `render`.
+fn parse_argument(argument: &str) -> std::result::Result<(String, Value), String> {
+    let (name, value) = argument
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=VALUE, got `{}`", argument))?;
+    Ok((name.to_string(), Value::String(value.to_string())))
+}
+
+fn generate_types(
+    input_dir: PathBuf,
+    output_file: PathBuf,
+    pattern: Option<String>,
+    source_locale: &str,
+    dialect: Dialect,
+) -> Result<()> {
+    let mut generator = match pattern {
+        Some(pattern) => {
+            let parsed = CatalogPattern::new(&pattern).map_err(|error| {
+                miette::Report::new(error)
+                    .with_source_code(miette::NamedSource::new("--pattern", pattern.clone()))
+            })?;
+            TypeScriptGenerator::with_pattern(dialect, parsed)
+        }
+        None => TypeScriptGenerator::with_dialect(dialect),
+    };
+    generator.set_source_locale(source_locale);
     generator
         .process_directory(input_dir.to_str().unwrap())
         .into_diagnostic()
@@ -1,213 +1,1195 @@
-use crate::lex::{Lexer, Token};
-use serde_json::{json, Value as JsonValue};
-use std::collections::HashMap;
+use crate::dialect::{Dialect, Formatter};
+use crate::error::{ParseError, ParseErrorKind};
+use crate::fluent;
+use crate::lex::{Lexer, Token, TokenKind};
+use crate::span::Span;
+use indexmap::IndexMap;
+use std::iter::Peekable;
 
+/// The `key {message}` options of a plural, selectordinal or select argument,
+/// in source order.
+pub type Options = IndexMap<String, Vec<AstNode>>;
+
+/// A parsed message. Every node carries the byte span it was parsed from,
+/// relative to the start of the message.
 #[derive(Debug, PartialEq, Clone)]
 pub enum AstNode {
-    Root(Vec<AstNode>),
-    Text(String),
-    Variable(String),
+    Root(Vec<AstNode>, Span),
+    Text(String, Span),
+    Variable(String, Span),
     Plural {
         variable: String,
-        options: HashMap<String, Vec<AstNode>>,
+        offset: u32,
+        options: Options,
+        span: Span,
+    },
+    SelectOrdinal {
+        variable: String,
+        options: Options,
+        span: Span,
     },
     Select {
         variable: String,
-        options: HashMap<String, Vec<AstNode>>,
+        options: Options,
+        span: Span,
     },
+    /// `{price, number}` or `{price, number, ::currency/EUR}`. The style is
+    /// either a named style (`integer`, `percent`) or a `::` skeleton.
+    Number {
+        variable: String,
+        style: Option<String>,
+        span: Span,
+    },
+    /// `{when, date, short}`
+    Date {
+        variable: String,
+        style: Option<String>,
+        span: Span,
+    },
+    /// `{when, time, short}`
+    Time {
+        variable: String,
+        style: Option<String>,
+        span: Span,
+    },
+    /// `<b>...</b>`, `<a href="/terms">...</a>`, `<br/>`, or react-i18next's
+    /// numbered `<0>...</0>` and `<1/>`. Self-closing tags and HTML void
+    /// elements such as `<br>` have no children.
     HtmlTag {
         name: String,
+        attributes: Vec<Attribute>,
+        /// The name is an index into the components passed to `<Trans>`.
+        numbered: bool,
+        self_closing: bool,
         children: Vec<AstNode>,
+        span: Span,
+    },
+    /// i18next `{{name}}`, `{{- name}}` (unescaped) or
+    /// `{{price, currency(USD)}}` with a chain of formatters.
+    Interpolation {
+        variable: String,
+        formatters: Vec<Formatter>,
+        escaped: bool,
+        span: Span,
     },
+    /// `#` inside a plural or selectordinal option: the argument's value minus
+    /// the plural offset.
+    Pound(Span),
+    /// A part of the message that failed to parse, produced by
+    /// `Parser::parse_recovering`. `recovered` is the best guess at what was
+    /// meant, e.g. a plural whose closing braces are still missing.
+    Error {
+        error: ParseError,
+        recovered: Option<Box<AstNode>>,
+        span: Span,
+    },
+}
+
+/// An attribute of an HTML tag, e.g. `href="/terms"`. Bare attributes such as
+/// `download` have no value.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Option<String>,
 }
 
-pub struct Parser {
-    tokens: Vec<Token>,
+/// Parses a message straight from the lexer's tokens, in one pass.
+pub struct Parser<'a> {
+    input: &'a str,
+    tokens: Peekable<Lexer<'a>>,
+    /// End of the last token taken.
+    end: usize,
+    dialect: Dialect,
 }
 
-impl Parser {
-    pub fn new(input: &str) -> Self {
-        let tokens: Vec<Token> = Lexer::new(input).collect();
-        Parser { tokens }
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Parser {
+            input,
+            tokens: Lexer::new(input).peekable(),
+            end: 0,
+            dialect: Dialect::Icu,
+        }
     }
 
-    pub fn parse(self) -> Result<AstNode, String> {
-        let (root, _) = self.parse_nodes(0)?;
-        Ok(root)
+    pub fn with_dialect(input: &'a str, dialect: Dialect) -> Self {
+        Parser {
+            input,
+            tokens: Lexer::with_dialect(input, dialect).peekable(),
+            end: 0,
+            dialect,
+        }
     }
 
-    fn parse_nodes(&self, mut position: usize) -> Result<(AstNode, usize), String> {
-        let mut nodes = Vec::new();
-        while position < self.tokens.len() {
-            let (node, new_position) = self.parse_node(position)?;
-            position = new_position;
-            match node {
-                Some(n) => nodes.push(n),
-                None => break,
-            }
+    /// Parses the message, failing on its first error.
+    pub fn parse(self) -> Result<AstNode, ParseError> {
+        let (ast, errors) = self.parse_recovering();
+        match errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(ast),
         }
-        Ok((AstNode::Root(nodes), position))
     }
 
-    fn parse_node(&self, position: usize) -> Result<(Option<AstNode>, usize), String> {
-        match &self.tokens[position] {
-            Token::Text(text) => Ok((Some(AstNode::Text(text.clone())), position + 1)),
-            Token::Variable(var) => Ok((
-                Some(AstNode::Variable(var[1..var.len() - 1].to_string())),
-                position + 1,
-            )),
-            Token::Plural(plural) => self.parse_plural(plural, position),
-            Token::Select(select) => self.parse_select(select, position),
-            Token::HtmlTag(tag) => self.parse_html_tag(tag, position),
+    /// Parses as much of the message as possible, for messages that are still
+    /// being edited. Broken arguments and tags become `AstNode::Error` nodes
+    /// holding whatever could be recovered; all errors are returned in source
+    /// order.
+    pub fn parse_recovering(mut self) -> (AstNode, Vec<ParseError>) {
+        if self.dialect == Dialect::Fluent {
+            return fluent::parse_pattern(self.input);
         }
+        let mut errors = Vec::new();
+        let nodes = self.parse_message(&mut errors);
+        errors.sort_by_key(|error| error.span.start);
+        (AstNode::Root(nodes, Span::new(0, self.input.len())), errors)
     }
 
-    fn parse_plural(
-        &self,
-        plural: &str,
-        position: usize,
-    ) -> Result<(Option<AstNode>, usize), String> {
-        let parts: Vec<&str> = plural[1..plural.len() - 1].splitn(3, ',').collect();
-        let variable = parts[0].trim().to_string();
-        let (options, _) = self.parse_options(parts[2])?;
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.next()?;
+        self.end = token.span.end;
+        Some(token)
+    }
 
-        Ok((Some(AstNode::Plural { variable, options }), position + 1))
+    fn peek(&mut self) -> Option<&TokenKind<'a>> {
+        self.tokens.peek().map(|token| &token.kind)
     }
 
-    fn parse_select(
-        &self,
-        select: &str,
-        position: usize,
-    ) -> Result<(Option<AstNode>, usize), String> {
-        let parts: Vec<&str> = select[1..select.len() - 1].splitn(3, ',').collect();
-        let variable = parts[0].trim().to_string();
-        let (options, _) = self.parse_options(parts[2])?;
+    /// Takes the next token if it is `kind`.
+    fn eat(&mut self, kind: TokenKind<'a>) -> Option<Token<'a>> {
+        if self.peek() == Some(&kind) {
+            self.next()
+        } else {
+            None
+        }
+    }
 
-        Ok((Some(AstNode::Select { variable, options }), position + 1))
+    fn word(&mut self) -> Option<Token<'a>> {
+        match self.peek() {
+            Some(TokenKind::Word(_)) => self.next(),
+            _ => None,
+        }
     }
 
-    fn parse_options(
-        &self,
-        options_str: &str,
-    ) -> Result<(HashMap<String, Vec<AstNode>>, usize), String> {
-        let mut options = HashMap::new();
-        let mut current_key = String::new();
-        let mut current_value = String::new();
-        let mut brace_count = 0;
-
-        for ch in options_str.chars() {
-            match ch {
-                '{' => {
-                    brace_count += 1;
-                    if brace_count > 1 {
-                        current_value.push(ch);
-                    }
+    /// Parses the whole message or one option message. Closing tags that
+    /// close nothing become error nodes.
+    fn parse_message(&mut self, errors: &mut Vec<ParseError>) -> Vec<AstNode> {
+        let mut nodes = Vec::new();
+        loop {
+            let (parsed, closing) = self.parse_nodes(errors);
+            nodes.extend(parsed);
+            let Some((name, span)) = closing else {
+                return nodes;
+            };
+            let error = ParseError::new(ParseErrorKind::UnexpectedClosingTag { name }, span);
+            nodes.push(error_node(error, None, errors));
+        }
+    }
+
+    /// Parses nodes up to the end of the input, the end of the option message
+    /// or a closing tag, which is returned by name along with its span.
+    fn parse_nodes(
+        &mut self,
+        errors: &mut Vec<ParseError>,
+    ) -> (Vec<AstNode>, Option<(String, Span)>) {
+        let mut nodes = Vec::new();
+        while !matches!(self.peek(), None | Some(TokenKind::OptionEnd)) {
+            let Some(Token { kind, span }) = self.next() else {
+                break;
+            };
+            if let Some(name) = closing_tag_name(&kind) {
+                return (nodes, Some((name, span)));
+            }
+            nodes.push(self.parse_node(kind, span, errors));
+        }
+        (nodes, None)
+    }
+
+    fn parse_node(
+        &mut self,
+        kind: TokenKind<'a>,
+        span: Span,
+        errors: &mut Vec<ParseError>,
+    ) -> AstNode {
+        match kind {
+            TokenKind::Text(text) => AstNode::Text(text.into_owned(), span),
+            TokenKind::Pound => AstNode::Pound(span),
+            TokenKind::HtmlTag(tag) => self.parse_html_tag(tag, span, errors),
+            TokenKind::Interpolation(interpolation) => {
+                let node = parse_interpolation(interpolation, span)
+                    .unwrap_or_else(|error| error_node(error, None, errors));
+                if interpolation.len() >= 4 && interpolation.ends_with("}}") {
+                    return node;
                 }
-                '}' => {
-                    brace_count -= 1;
-                    if brace_count == 0 {
-                        let sub_parser = Parser::new(&current_value);
-                        let sub_ast = sub_parser.parse()?;
-                        options.insert(current_key.trim().to_string(), sub_ast.into_vec());
-                        current_key.clear();
-                        current_value.clear();
-                    } else {
-                        current_value.push(ch);
-                    }
+                let error = ParseError::new(
+                    ParseErrorKind::UnclosedArgument,
+                    Span::new(span.start, span.start + 1),
+                );
+                error_node(error, Some(node), errors)
+            }
+            TokenKind::ArgumentStart => self.parse_argument(span, errors),
+            // The rest only follow an `ArgumentStart`, and `parse_argument`
+            // takes them all.
+            _ => AstNode::Text(self.input[span.start..span.end].to_string(), span),
+        }
+    }
+
+    /// Parses an argument after its opening `{`, up to and including the
+    /// closing `}`.
+    fn parse_argument(&mut self, open: Span, errors: &mut Vec<ParseError>) -> AstNode {
+        let name = self.word().map(|token| token.kind);
+        let mut keyword = None;
+        if self.eat(TokenKind::Comma).is_some() {
+            keyword = Some(match self.word().map(|token| token.kind) {
+                Some(TokenKind::Word(keyword)) => keyword,
+                _ => "",
+            });
+        }
+        let choice = match keyword {
+            Some("plural") => Some("plural"),
+            Some("selectordinal") => Some("selectordinal"),
+            Some("select") => Some("select"),
+            _ => None,
+        };
+
+        let mut style = None;
+        let mut choice_options = None;
+        let mut unclosed = None;
+        if self.eat(TokenKind::Comma).is_some() {
+            match choice {
+                Some(kind) => {
+                    let (options, error) = self.parse_options(kind, errors);
+                    choice_options = options;
+                    unclosed = error;
                 }
-                _ => {
-                    if brace_count == 0 {
-                        current_key.push(ch);
-                    } else {
-                        current_value.push(ch);
+                None => {
+                    if let Some(TokenKind::Style(found)) = self.peek() {
+                        style = Some(found.to_string());
+                        self.next();
                     }
                 }
             }
         }
 
-        Ok((options, options_str.len()))
+        let closed = unclosed.is_none() && self.eat(TokenKind::ArgumentEnd).is_some();
+        let end = if closed { self.end } else { self.input.len() };
+        let span = Span::new(open.start, end);
+
+        // The argument's own error comes before those of its contents.
+        let index = errors.len();
+        let node = match name {
+            // `{{name}}` is i18next; in ICU it would be a variable named
+            // `{name}`, so read it as `name` and report it.
+            Some(TokenKind::Word(variable)) if variable.contains(['{', '}']) => {
+                let node = self.argument_node(
+                    variable.trim_matches(['{', '}']),
+                    keyword,
+                    style,
+                    choice,
+                    choice_options,
+                    span,
+                    errors,
+                );
+                let error = ParseError::new(
+                    ParseErrorKind::BracedArgumentName {
+                        name: variable.to_string(),
+                    },
+                    span,
+                );
+                errors.insert(index, error.clone());
+                AstNode::Error {
+                    error,
+                    recovered: Some(Box::new(node)),
+                    span,
+                }
+            }
+            Some(TokenKind::Word(variable)) => self.argument_node(
+                variable,
+                keyword,
+                style,
+                choice,
+                choice_options,
+                span,
+                errors,
+            ),
+            _ => error_node(
+                ParseError::new(ParseErrorKind::EmptyArgument, span),
+                None,
+                errors,
+            ),
+        };
+
+        if closed {
+            return node;
+        }
+        let error = unclosed.unwrap_or_else(|| {
+            ParseError::new(
+                ParseErrorKind::UnclosedArgument,
+                Span::new(open.start, open.start + 1),
+            )
+        });
+        errors.insert(index, error.clone());
+        AstNode::Error {
+            error,
+            recovered: Some(Box::new(node)),
+            span,
+        }
     }
 
-    fn parse_html_tag(
+    #[allow(clippy::too_many_arguments)]
+    fn argument_node(
         &self,
-        tag: &str,
-        position: usize,
-    ) -> Result<(Option<AstNode>, usize), String> {
-        if tag.starts_with("</") {
-            return Ok((None, position + 1));
+        variable: &str,
+        keyword: Option<&str>,
+        style: Option<String>,
+        choice: Option<&'static str>,
+        choice_options: Option<(u32, Options)>,
+        span: Span,
+        errors: &mut Vec<ParseError>,
+    ) -> AstNode {
+        let variable = variable.to_string();
+        let Some(kind) = choice else {
+            return match keyword {
+                Some("number") => AstNode::Number {
+                    variable,
+                    style,
+                    span,
+                },
+                Some("date") => AstNode::Date {
+                    variable,
+                    style,
+                    span,
+                },
+                Some("time") => AstNode::Time {
+                    variable,
+                    style,
+                    span,
+                },
+                _ => AstNode::Variable(variable, span),
+            };
+        };
+
+        let error = match &choice_options {
+            None => Some(ParseErrorKind::MissingOptions {
+                variable: variable.clone(),
+                kind,
+            }),
+            Some((_, options)) if !options.contains_key("other") => {
+                Some(ParseErrorKind::MissingOther {
+                    variable: variable.clone(),
+                    kind,
+                })
+            }
+            Some(_) => None,
+        };
+
+        let (offset, options) = choice_options.unwrap_or_default();
+        let node = match kind {
+            "plural" => AstNode::Plural {
+                variable,
+                offset,
+                options,
+                span,
+            },
+            "selectordinal" => AstNode::SelectOrdinal {
+                variable,
+                options,
+                span,
+            },
+            _ => AstNode::Select {
+                variable,
+                options,
+                span,
+            },
+        };
+
+        match error {
+            Some(kind) => error_node(ParseError::new(kind, span), Some(node), errors),
+            None => node,
         }
+    }
 
-        let name = tag[1..tag.len() - 1].to_string();
-        let (children, new_position) = self.parse_nodes(position + 1)?;
+    /// Parses the `key {message}` options of a `kind` argument, with the
+    /// offset of a plural. Returns `None` if there are none at all, and the
+    /// error for an option message that runs to the end of the input. Keys
+    /// are checked against the CLDR categories for plural and selectordinal
+    /// arguments.
+    fn parse_options(
+        &mut self,
+        kind: &'static str,
+        errors: &mut Vec<ParseError>,
+    ) -> (Option<(u32, Options)>, Option<ParseError>) {
+        let categories: Option<(&'static str, &[&str])> = match kind {
+            "plural" => Some(("plural", &PLURAL_CATEGORIES)),
+            "selectordinal" => Some(("ordinal", &PLURAL_CATEGORIES)),
+            _ => None,
+        };
 
-        match children {
-            AstNode::Root(child_nodes) => Ok((
-                Some(AstNode::HtmlTag {
-                    name,
-                    children: child_nodes,
-                }),
-                new_position,
+        let mut offset = 0;
+        let mut options = IndexMap::new();
+        let mut empty = true;
+        loop {
+            let (key, key_span) = match self.peek() {
+                Some(TokenKind::Word(_)) => match self.next() {
+                    Some(Token {
+                        kind: TokenKind::Word(key),
+                        span,
+                    }) => (key, span),
+                    _ => break,
+                },
+                Some(TokenKind::OptionStart) => {
+                    let start = self
+                        .tokens
+                        .peek()
+                        .map_or(self.end, |token| token.span.start);
+                    ("", Span::new(start, start))
+                }
+                _ => break,
+            };
+
+            if let Some(value) = key
+                .strip_prefix("offset:")
+                .filter(|_| kind == "plural" && empty)
+            {
+                offset = self.parse_offset(value, key_span, errors);
+                empty = false;
+                continue;
+            }
+            empty = false;
+
+            if let Some((kind, categories)) = categories {
+                if let Err(error) = validate_category(key, key_span, kind, categories) {
+                    errors.push(error);
+                }
+            }
+
+            if self.eat(TokenKind::OptionStart).is_none() {
+                errors.push(ParseError::new(
+                    ParseErrorKind::MissingOptionBody {
+                        key: key.to_string(),
+                    },
+                    key_span,
+                ));
+                continue;
+            }
+
+            let body = self.parse_message(errors);
+            options.insert(key.to_string(), body);
+            if self.eat(TokenKind::OptionEnd).is_none() {
+                let error = ParseError::new(
+                    ParseErrorKind::UnclosedOption {
+                        key: key.to_string(),
+                    },
+                    Span::new(key_span.start, self.input.len()),
+                );
+                return (Some((offset, options)), Some(error));
+            }
+        }
+
+        if empty {
+            (None, None)
+        } else {
+            (Some((offset, options)), None)
+        }
+    }
+
+    /// Parses the `N` of a plural's `offset:N`, which may also be written
+    /// `offset: N`.
+    fn parse_offset(&mut self, value: &str, span: Span, errors: &mut Vec<ParseError>) -> u32 {
+        let (value, span) = if value.is_empty() {
+            match self.word() {
+                Some(Token {
+                    kind: TokenKind::Word(value),
+                    span,
+                }) => (value, span),
+                _ => (value, Span::new(span.end, span.end)),
+            }
+        } else {
+            (value, Span::new(span.end - value.len(), span.end))
+        };
+
+        value.parse().unwrap_or_else(|_| {
+            errors.push(ParseError::new(
+                ParseErrorKind::InvalidOffset {
+                    offset: value.to_string(),
+                },
+                span,
+            ));
+            0
+        })
+    }
+
+    fn parse_html_tag(&mut self, tag: &str, span: Span, errors: &mut Vec<ParseError>) -> AstNode {
+        let (name, attributes, self_closing) = split_tag(tag);
+        let numbered = !name.is_empty() && name.chars().all(|c| c.is_ascii_digit());
+
+        if self_closing || VOID_ELEMENTS.contains(&name.to_ascii_lowercase().as_str()) {
+            return AstNode::HtmlTag {
+                name,
+                attributes,
+                numbered,
+                self_closing: true,
+                children: Vec::new(),
+                span,
+            };
+        }
+
+        let (children, closing) = self.parse_nodes(errors);
+        let error = match closing {
+            Some((closing, _)) if closing == name => None,
+            Some((found, closing_span)) => Some(ParseError::new(
+                ParseErrorKind::MismatchedTag {
+                    expected: name.clone(),
+                    found,
+                },
+                closing_span,
             )),
-            _ => Err(format!(
-                "Expected Root node for HTML tag children at position {}",
-                position
+            None => Some(ParseError::new(
+                ParseErrorKind::UnclosedTag { name: name.clone() },
+                span,
             )),
+        };
+        let node = AstNode::HtmlTag {
+            name,
+            attributes,
+            numbered,
+            self_closing: false,
+            children,
+            span: Span::new(span.start, self.end),
+        };
+
+        match error {
+            Some(error) => error_node(error, Some(node), errors),
+            None => node,
         }
     }
 }
 
-impl AstNode {
-    fn into_vec(self) -> Vec<AstNode> {
-        if let AstNode::Root(nodes) = self {
-            nodes
-        } else {
-            vec![self]
+/// Records `error` and wraps whatever could be recovered around it.
+fn error_node(
+    error: ParseError,
+    recovered: Option<AstNode>,
+    errors: &mut Vec<ParseError>,
+) -> AstNode {
+    errors.push(error.clone());
+    let span = recovered.as_ref().map_or(error.span, AstNode::span);
+    AstNode::Error {
+        error,
+        recovered: recovered.map(Box::new),
+        span,
+    }
+}
+
+/// HTML elements that never have content or a closing tag.
+const VOID_ELEMENTS: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// The name of a closing tag such as `</b>`.
+fn closing_tag_name(kind: &TokenKind) -> Option<String> {
+    let TokenKind::HtmlTag(tag) = kind else {
+        return None;
+    };
+    let name = tag.strip_prefix("</")?;
+    Some(name.strip_suffix('>').unwrap_or(name).trim().to_string())
+}
+
+/// Splits an opening tag such as `<a href="/terms" download/>` into its name,
+/// its attributes and whether it closes itself.
+fn split_tag(tag: &str) -> (String, Vec<Attribute>, bool) {
+    let inner = tag.strip_prefix('<').unwrap_or(tag);
+    let inner = inner.strip_suffix('>').unwrap_or(inner).trim_end();
+    let (inner, self_closing) = match inner.strip_suffix('/') {
+        Some(inner) => (inner, true),
+        None => (inner, false),
+    };
+    let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+    (
+        inner[..name_end].to_string(),
+        parse_attributes(&inner[name_end..]),
+        self_closing,
+    )
+}
+
+/// Parses `name="value" name='value' name=value name` attribute lists.
+fn parse_attributes(mut rest: &str) -> Vec<Attribute> {
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return attributes;
+        }
+        let name_end = rest
+            .find(|c: char| c.is_whitespace() || c == '=')
+            .unwrap_or(rest.len());
+        let name = rest[..name_end].to_string();
+        rest = rest[name_end..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, remainder) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let body = &after[1..];
+                        let end = body.find(quote).unwrap_or(body.len());
+                        (&body[..end], body.get(end + 1..).unwrap_or_default())
+                    }
+                    _ => {
+                        let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                        (&after[..end], &after[end..])
+                    }
+                };
+                rest = remainder;
+                Some(value.to_string())
+            }
+            None => None,
+        };
+        attributes.push(Attribute { name, value });
+    }
+}
+
+/// Parses the inside of an i18next `{{...}}` interpolation.
+fn parse_interpolation(interpolation: &str, span: Span) -> Result<AstNode, ParseError> {
+    let inner = interpolation.strip_prefix("{{").unwrap_or(interpolation);
+    let inner = inner
+        .strip_suffix("}}")
+        .or_else(|| inner.strip_suffix('}'))
+        .unwrap_or(inner)
+        .trim();
+    let (escaped, inner) = match inner.strip_prefix('-') {
+        Some(unescaped) => (false, unescaped.trim_start()),
+        None => (true, inner),
+    };
+
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in inner.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&inner[start..index]);
+                start = index + 1;
+            }
+            _ => {}
         }
     }
+    parts.push(&inner[start..]);
+
+    let variable = parts[0].trim().to_string();
+    if variable.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::EmptyArgument, span));
+    }
+
+    Ok(AstNode::Interpolation {
+        variable,
+        formatters: parts[1..]
+            .iter()
+            .filter(|formatter| !formatter.trim().is_empty())
+            .map(|formatter| Formatter::parse(formatter))
+            .collect(),
+        escaped,
+        span,
+    })
+}
+
+/// The CLDR plural categories, the keys `plural` and `selectordinal`
+/// arguments may select on besides `=N`. Cardinal and ordinal rules share
+/// these keywords; which of them a locale actually uses is checked against
+/// its rules by `plural::check_categories`.
+pub const PLURAL_CATEGORIES: [&str; 6] = ["zero", "one", "two", "few", "many", "other"];
+
+fn validate_category(
+    key: &str,
+    span: Span,
+    kind: &'static str,
+    categories: &[&str],
+) -> Result<(), ParseError> {
+    let is_exact = key
+        .strip_prefix('=')
+        .is_some_and(|n| n.parse::<f64>().is_ok());
+    if !is_exact && !categories.contains(&key) {
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidCategory {
+                category: key.to_string(),
+                kind,
+            },
+            span,
+        ));
+    }
+    Ok(())
 }
 
 impl AstNode {
-    pub fn to_json(&self) -> JsonValue {
+    /// The byte span of this node in the message.
+    pub fn span(&self) -> Span {
         match self {
-            AstNode::Root(nodes) => {
-                json!({
-                    "type": "root",
-                    "children": nodes.iter().map(|node| node.to_json()).collect::<Vec<JsonValue>>()
-                })
-            }
-            AstNode::Text(text) => {
-                json!({
-                    "type": "text",
-                    "value": text
-                })
-            }
-            AstNode::Variable(var) => {
-                json!({
-                    "type": "variable",
-                    "name": var
-                })
-            }
-            AstNode::Plural { variable, options } => {
-                json!({
-                    "type": "plural",
-                    "variable": variable,
-                    "options": options.iter().map(|(k, v)| (k.clone(), json!(v.iter().map(|node| node.to_json()).collect::<Vec<JsonValue>>()))).collect::<serde_json::Map<String, JsonValue>>()
-                })
-            }
-            AstNode::Select { variable, options } => {
-                json!({
-                    "type": "select",
-                    "variable": variable,
-                    "options": options.iter().map(|(k, v)| (k.clone(), json!(v.iter().map(|node| node.to_json()).collect::<Vec<JsonValue>>()))).collect::<serde_json::Map<String, JsonValue>>()
-                })
+            AstNode::Root(_, span)
+            | AstNode::Text(_, span)
+            | AstNode::Variable(_, span)
+            | AstNode::Pound(span)
+            | AstNode::Plural { span, .. }
+            | AstNode::SelectOrdinal { span, .. }
+            | AstNode::Select { span, .. }
+            | AstNode::Number { span, .. }
+            | AstNode::Date { span, .. }
+            | AstNode::Time { span, .. }
+            | AstNode::HtmlTag { span, .. }
+            | AstNode::Interpolation { span, .. }
+            | AstNode::Error { span, .. } => *span,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(message: &str) -> Vec<AstNode> {
+        match Parser::new(message).parse().unwrap() {
+            AstNode::Root(nodes, _) => nodes,
+            ast => panic!("expected a root, got {:?}", ast),
+        }
+    }
+
+    #[test]
+    fn parses_selectordinal_into_its_own_node() {
+        let nodes = parse("{pos, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}");
+        let [AstNode::SelectOrdinal {
+            variable, options, ..
+        }] = &nodes[..]
+        else {
+            panic!("expected a selectordinal, got {:?}", nodes);
+        };
+        assert_eq!(variable, "pos");
+        let mut keys: Vec<_> = options.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["few", "one", "other", "two"]);
+
+        let nodes = parse("{count, plural, one {# item} other {# items}}");
+        assert!(
+            matches!(&nodes[..], [AstNode::Plural { .. }]),
+            "{:?}",
+            nodes
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_ordinal_categories() {
+        let error = Parser::new("{pos, selectordinal, first {#st} other {#th}}")
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::InvalidCategory {
+                category: "first".into(),
+                kind: "ordinal",
             }
-            AstNode::HtmlTag { name, children } => {
-                json!({
-                    "type": "html_tag",
-                    "name": name,
-                    "children": children.iter().map(|node| node.to_json()).collect::<Vec<JsonValue>>()
+        );
+        assert!(Parser::new("{pos, selectordinal, =1 {first} other {#th}}")
+            .parse()
+            .is_ok());
+    }
+
+    #[test]
+    fn reads_the_plural_offset() {
+        let nodes = parse(
+            "{n, plural, offset:1 =0 {nobody} one {you and # other} other {you and # others}}",
+        );
+        let [AstNode::Plural {
+            variable,
+            offset,
+            options,
+            ..
+        }] = &nodes[..]
+        else {
+            panic!("expected a plural, got {:?}", nodes);
+        };
+        assert_eq!((variable.as_str(), *offset), ("n", 1));
+        let mut keys: Vec<_> = options.keys().map(String::as_str).collect();
+        keys.sort();
+        assert_eq!(keys, ["=0", "one", "other"]);
+
+        let nodes = parse("{n, plural, one {#} other {#}}");
+        assert!(matches!(&nodes[..], [AstNode::Plural { offset: 0, .. }]));
+
+        let error = Parser::new("{n, plural, offset:x one {#} other {#}}")
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::InvalidOffset { offset: "x".into() }
+        );
+    }
+
+    #[test]
+    fn reads_pound_only_in_plural_options() {
+        let options = |nodes: &[AstNode]| match nodes {
+            [AstNode::Plural { options, .. }
+            | AstNode::SelectOrdinal { options, .. }
+            | AstNode::Select { options, .. }] => options["other"].clone(),
+            _ => panic!("expected one argument, got {:?}", nodes),
+        };
+        let pound = |nodes: &[AstNode]| nodes.iter().any(|node| matches!(node, AstNode::Pound(_)));
+
+        let plural = parse("{n, plural, other {# items}}");
+        assert!(matches!(options(&plural)[0], AstNode::Pound(_)));
+        assert!(pound(&options(&parse("{n, selectordinal, other {#th}}"))));
+        assert!(!pound(&options(&parse("{g, select, other {# is text}}"))));
+        assert!(!pound(&parse("Item #1")));
+    }
+
+    #[test]
+    fn parses_typed_arguments_with_their_style() {
+        let style = |style: &str| Some(style.to_string());
+        assert_eq!(
+            parse("{price, number, ::currency/EUR} on {day, date, short} at {at, time}"),
+            [
+                AstNode::Number {
+                    variable: "price".into(),
+                    style: style("::currency/EUR"),
+                    span: Span::new(0, 31),
+                },
+                AstNode::Text(" on ".into(), Span::new(31, 35)),
+                AstNode::Date {
+                    variable: "day".into(),
+                    style: style("short"),
+                    span: Span::new(35, 53),
+                },
+                AstNode::Text(" at ".into(), Span::new(53, 57)),
+                AstNode::Time {
+                    variable: "at".into(),
+                    style: None,
+                    span: Span::new(57, 67),
+                },
+            ]
+        );
+        assert_eq!(
+            parse("{ratio,number,percent}"),
+            [AstNode::Number {
+                variable: "ratio".into(),
+                style: style("percent"),
+                span: Span::new(0, 22),
+            }]
+        );
+    }
+
+    #[test]
+    fn reads_quoted_literals_as_text() {
+        let text = |nodes: &[AstNode]| {
+            nodes
+                .iter()
+                .map(|node| match node {
+                    AstNode::Text(text, _) => text.as_str(),
+                    AstNode::Pound(_) => "#",
+                    _ => panic!("expected text, got {:?}", node),
                 })
-            }
+                .collect::<String>()
+        };
+        assert_eq!(
+            text(&parse("It''s '{name}' and '<b>'")),
+            "It's {name} and <b>"
+        );
+        assert_eq!(text(&parse("don't worry")), "don't worry");
+
+        let nodes = parse("{n, plural, other {'{'# '}' '#' it''s}}");
+        let [AstNode::Plural { options, .. }] = &nodes[..] else {
+            panic!("expected a plural, got {:?}", nodes);
+        };
+        assert!(matches!(options["other"][1], AstNode::Pound(_)));
+        assert_eq!(text(&options["other"]), "{# } # it's");
+    }
+
+    #[test]
+    fn reports_braced_argument_name_in_icu() {
+        let (ast, errors) = Parser::new("Hi {{name}}!").parse_recovering();
+        let AstNode::Root(nodes, _) = ast else {
+            panic!("expected a root, got {:?}", ast);
+        };
+        assert!(matches!(
+            &nodes[1],
+            AstNode::Error { recovered: Some(node), .. }
+                if matches!(node.as_ref(), AstNode::Variable(name, _) if name == "name")
+        ));
+        let [error] = &errors[..] else {
+            panic!("expected one error, got {:?}", errors);
+        };
+        assert_eq!(
+            *error,
+            ParseError::new(
+                ParseErrorKind::BracedArgumentName {
+                    name: "{name}".into()
+                },
+                Span::new(3, 11),
+            )
+        );
+        assert!(error.help().contains("dialect: \"i18next\""));
+    }
+
+    #[test]
+    fn accepts_double_braces_in_i18next() {
+        let root = Parser::with_dialect("Hi {{name}}!", Dialect::I18next).parse();
+        assert!(root.is_ok(), "{:?}", root);
+    }
+
+    #[test]
+    fn spans_are_relative_to_the_message() {
+        let message = "Hi {name}, {n, plural, one {# <b>item</b>} other {#}}";
+        let nodes = parse(message);
+        let slices: Vec<_> = nodes
+            .iter()
+            .map(|node| &message[node.span().start..node.span().end])
+            .collect();
+        assert_eq!(
+            slices,
+            [
+                "Hi ",
+                "{name}",
+                ", ",
+                "{n, plural, one {# <b>item</b>} other {#}}"
+            ]
+        );
+
+        let AstNode::Plural { options, .. } = &nodes[3] else {
+            panic!("expected a plural, got {:?}", nodes[3]);
+        };
+        let one: Vec<_> = options["one"]
+            .iter()
+            .map(|node| &message[node.span().start..node.span().end])
+            .collect();
+        assert_eq!(one, ["#", " ", "<b>item</b>"]);
+        let AstNode::HtmlTag { children, .. } = &options["one"][2] else {
+            panic!("expected a tag, got {:?}", options["one"][2]);
+        };
+        assert_eq!(children[0].span(), Span::new(33, 37));
+        assert_eq!(
+            Parser::new(message).parse().unwrap().span(),
+            Span::new(0, message.len())
+        );
+    }
+
+    #[test]
+    fn returns_errors_for_malformed_messages() {
+        let cases = [
+            ("{name", ParseErrorKind::UnclosedArgument, Span::new(0, 1)),
+            ("{ }", ParseErrorKind::EmptyArgument, Span::new(0, 3)),
+            (
+                "{n, plural}",
+                ParseErrorKind::MissingOptions {
+                    variable: "n".into(),
+                    kind: "plural",
+                },
+                Span::new(0, 11),
+            ),
+            (
+                "{g, select, other {x} male}",
+                ParseErrorKind::MissingOptionBody { key: "male".into() },
+                Span::new(22, 26),
+            ),
+            (
+                "{n, plural, other {{}}}",
+                ParseErrorKind::EmptyArgument,
+                Span::new(19, 21),
+            ),
+        ];
+        for (message, kind, span) in cases {
+            assert_eq!(
+                Parser::new(message).parse(),
+                Err(ParseError::new(kind, span)),
+                "{}",
+                message
+            );
         }
     }
+
+    #[test]
+    fn errors_are_diagnostics() {
+        use miette::Diagnostic;
+
+        let error = Parser::new("{n, plural, offset:x other {#}}")
+            .parse()
+            .unwrap_err();
+        assert_eq!(
+            Diagnostic::code(&error).unwrap().to_string(),
+            "typed_key::invalid_offset"
+        );
+        assert!(Diagnostic::help(&error).is_some());
+        let labels: Vec<_> = error.labels().unwrap().collect();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].label(), Some("invalid plural offset `x`"));
+    }
+
+    #[test]
+    fn recovers_what_was_meant_around_errors() {
+        let (ast, errors) = Parser::new("Hi {name").parse_recovering();
+        assert_eq!(
+            errors,
+            [ParseError::new(
+                ParseErrorKind::UnclosedArgument,
+                Span::new(3, 4)
+            )]
+        );
+        assert_eq!(
+            ast,
+            AstNode::Root(
+                vec![
+                    AstNode::Text("Hi ".into(), Span::new(0, 3)),
+                    AstNode::Error {
+                        error: errors[0].clone(),
+                        recovered: Some(Box::new(AstNode::Variable(
+                            "name".into(),
+                            Span::new(3, 8)
+                        ))),
+                        span: Span::new(3, 8),
+                    },
+                ],
+                Span::new(0, 8),
+            )
+        );
+
+        let (ast, _) = Parser::new("{n, plural, one {# item} other {# items}").parse_recovering();
+        let AstNode::Root(nodes, _) = ast else {
+            panic!("expected a root, got {:?}", ast);
+        };
+        assert!(
+            matches!(
+                &nodes[..],
+                [AstNode::Error { recovered: Some(node), .. }]
+                    if matches!(node.as_ref(), AstNode::Plural { options, .. } if options.len() == 2)
+            ),
+            "{:?}",
+            nodes
+        );
+    }
+
+    #[test]
+    fn collects_every_error_in_source_order() {
+        let (ast, errors) = Parser::new("{n, plural, one {x}} and {").parse_recovering();
+        let kinds: Vec<_> = errors.iter().map(|error| &error.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                &ParseErrorKind::MissingOther {
+                    variable: "n".into(),
+                    kind: "plural",
+                },
+                &ParseErrorKind::UnclosedArgument,
+                &ParseErrorKind::EmptyArgument,
+            ]
+        );
+        let AstNode::Root(nodes, _) = ast else {
+            panic!("expected a root, got {:?}", ast);
+        };
+        assert_eq!(nodes[1], AstNode::Text(" and ".into(), Span::new(20, 25)));
+        assert_eq!(
+            Parser::new("{n, plural, one {x}} and {").parse(),
+            Err(errors[0].clone())
+        );
+    }
+
+    #[test]
+    fn reports_mismatched_and_stray_closing_tags() {
+        let errors = |message: &str| Parser::new(message).parse_recovering().1;
+        assert_eq!(
+            errors("<b>bold</i>"),
+            [ParseError::new(
+                ParseErrorKind::MismatchedTag {
+                    expected: "b".into(),
+                    found: "i".into(),
+                },
+                Span::new(7, 11),
+            )]
+        );
+        assert_eq!(
+            errors("a </b> c"),
+            [ParseError::new(
+                ParseErrorKind::UnexpectedClosingTag { name: "b".into() },
+                Span::new(2, 6),
+            )]
+        );
+        assert_eq!(
+            errors("1 < 2 <b>x"),
+            [ParseError::new(
+                ParseErrorKind::UnclosedTag { name: "b".into() },
+                Span::new(6, 9),
+            )]
+        );
+    }
+
+    #[test]
+    fn parses_tag_attributes() {
+        let nodes = parse("<a href=\"/x?a>b\" target='_blank' disabled>link</a>");
+        let [AstNode::HtmlTag {
+            name,
+            attributes,
+            children,
+            ..
+        }] = &nodes[..]
+        else {
+            panic!("expected a tag, got {:?}", nodes);
+        };
+        let attribute = |name: &str, value: Option<&str>| Attribute {
+            name: name.into(),
+            value: value.map(Into::into),
+        };
+        assert_eq!(name, "a");
+        assert_eq!(
+            attributes,
+            &[
+                attribute("href", Some("/x?a>b")),
+                attribute("target", Some("_blank")),
+                attribute("disabled", None),
+            ]
+        );
+        assert_eq!(children, &[AstNode::Text("link".into(), Span::new(42, 46))]);
+    }
+
+    #[test]
+    fn parses_self_closing_and_numbered_tags() {
+        let tags: Vec<_> = parse("<br/> <br> <0>zero</0> <1/>")
+            .into_iter()
+            .filter_map(|node| match node {
+                AstNode::HtmlTag {
+                    name,
+                    numbered,
+                    self_closing,
+                    children,
+                    ..
+                } => Some((name, numbered, self_closing, children.len())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            tags,
+            [
+                ("br".into(), false, true, 0),
+                ("br".into(), false, true, 0),
+                ("0".into(), true, false, 1),
+                ("1".into(), true, true, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_options_in_source_order() {
+        let keys = |message: &str| match &parse(message)[..] {
+            [AstNode::Plural { options, .. }
+            | AstNode::SelectOrdinal { options, .. }
+            | AstNode::Select { options, .. }] => options.keys().cloned().collect::<Vec<_>>(),
+            nodes => panic!("expected one argument, got {:?}", nodes),
+        };
+        assert_eq!(
+            keys("{n, plural, other {#} =0 {none} one {#} =1 {single}}"),
+            ["other", "=0", "one", "=1"]
+        );
+        assert_eq!(
+            keys("{g, select, male {he} other {they} female {she}}"),
+            ["male", "other", "female"]
+        );
+
+        let json = parse("{g, select, male {he} other {they} female {she}}")[0].to_json();
+        let json_keys: Vec<_> = json["ast"]["options"].as_object().unwrap().keys().collect();
+        assert_eq!(json_keys, ["male", "other", "female"]);
+    }
 }
@@ -0,0 +1,534 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use crate::error::{ParseError, ParseErrorKind};
+use crate::parse::{AstNode, Options};
+use crate::span::Span;
+use crate::visit::{walk_options, Visit};
+
+mod data;
+
+/// A CLDR plural category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub const ALL: [PluralCategory; 6] = [
+        PluralCategory::Zero,
+        PluralCategory::One,
+        PluralCategory::Two,
+        PluralCategory::Few,
+        PluralCategory::Many,
+        PluralCategory::Other,
+    ];
+
+    /// The option key for the category, e.g. `few`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PluralCategory::Zero => "zero",
+            PluralCategory::One => "one",
+            PluralCategory::Two => "two",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for PluralCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether a number counts things (`plural`) or ranks them (`selectordinal`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluralKind {
+    Cardinal,
+    Ordinal,
+}
+
+/// The CLDR plural operands of a number as written, e.g. `1.50`: its value
+/// `n`, integer digits `i`, count of visible fraction digits with and without
+/// trailing zeros `v` and `w`, those fraction digits `f` and `t`, and the
+/// compact exponent `e` (also `c`) of a number such as `1.2c6`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Operands {
+    n: f64,
+    i: u64,
+    v: u64,
+    w: u64,
+    f: u64,
+    t: u64,
+    e: u64,
+}
+
+impl Operands {
+    fn get(&self, operand: char) -> f64 {
+        match operand {
+            'n' => self.n,
+            'i' => self.i as f64,
+            'v' => self.v as f64,
+            'w' => self.w as f64,
+            'f' => self.f as f64,
+            't' => self.t as f64,
+            'e' | 'c' => self.e as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Reads a decimal number such as `-1.50` or `1.2c6`, keeping its visible
+/// fraction digits.
+impl FromStr for Operands {
+    type Err = ();
+
+    fn from_str(number: &str) -> Result<Self, Self::Err> {
+        let (number, exponent) = match number.split_once(['c', 'e']) {
+            Some((number, exponent)) => (number, exponent.parse::<usize>().map_err(|_| ())?),
+            None => (number, 0),
+        };
+        let digits = number.trim_start_matches(['-', '+']);
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if !integer
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+        {
+            return Err(());
+        }
+        // The exponent moves fraction digits into the integer: `1.2c3` is 1200.
+        let shift = exponent.min(fraction.len());
+        let integer = format!(
+            "{}{}{}",
+            integer,
+            &fraction[..shift],
+            "0".repeat(exponent - shift)
+        );
+        let fraction = &fraction[shift..];
+        let n: f64 = format!("0{}.{}", integer, fraction)
+            .parse()
+            .map_err(|_| ())?;
+        let trimmed = fraction.trim_end_matches('0');
+        let value = |digits: &str| digits.parse().unwrap_or(u64::MAX);
+        Ok(Operands {
+            n,
+            i: if integer.is_empty() {
+                0
+            } else {
+                value(&integer)
+            },
+            v: fraction.len() as u64,
+            w: trimmed.len() as u64,
+            f: if fraction.is_empty() {
+                0
+            } else {
+                value(fraction)
+            },
+            t: if trimmed.is_empty() {
+                0
+            } else {
+                value(trimmed)
+            },
+            e: exponent as u64,
+        })
+    }
+}
+
+/// A number without trailing fraction zeros: `1.0` is `1`.
+impl From<f64> for Operands {
+    fn from(n: f64) -> Self {
+        format!("{}", n).parse().unwrap_or(Operands {
+            n: n.abs(),
+            i: n.abs().trunc() as u64,
+            v: 0,
+            w: 0,
+            f: 0,
+            t: 0,
+            e: 0,
+        })
+    }
+}
+
+/// The plural rules of a locale.
+///
+/// A locale such as `pt-PT` or `fr_CA` uses the rules of its region if CLDR
+/// has them, and those of its language otherwise. A language CLDR doesn't know
+/// is logged and only uses `other`.
+#[derive(Debug, Clone)]
+pub struct PluralRules {
+    rules: Vec<(PluralCategory, Condition)>,
+}
+
+impl PluralRules {
+    pub fn new(locale: &str, kind: PluralKind) -> Self {
+        let table = match kind {
+            PluralKind::Cardinal => data::CARDINAL,
+            PluralKind::Ordinal => data::ORDINAL,
+        };
+        let locale = locale.replace('_', "-");
+        let language = locale.split('-').next().unwrap_or_default();
+        let find = |table: &[(&str, &'static str)]| {
+            [locale.as_str(), language].into_iter().find_map(|locale| {
+                table
+                    .binary_search_by(|(candidate, _)| (*candidate).cmp(locale))
+                    .ok()
+                    .map(|index| table[index].1)
+            })
+        };
+        let rules = match find(table) {
+            Some(rules) => parse_rules(rules),
+            None => {
+                if find(data::CARDINAL).is_none() {
+                    warn_unknown_locale(&locale);
+                }
+                Vec::new()
+            }
+        };
+        PluralRules { rules }
+    }
+
+    pub fn cardinal(locale: &str) -> Self {
+        Self::new(locale, PluralKind::Cardinal)
+    }
+
+    pub fn ordinal(locale: &str) -> Self {
+        Self::new(locale, PluralKind::Ordinal)
+    }
+
+    /// The categories the locale uses, in CLDR order with `other` last.
+    pub fn categories(&self) -> Vec<PluralCategory> {
+        let mut categories: Vec<_> = self.rules.iter().map(|(category, _)| *category).collect();
+        categories.push(PluralCategory::Other);
+        categories
+    }
+
+    /// The category of `number`, e.g. `few` for 3 in Polish. Where visible
+    /// fraction digits matter, pass `Operands` parsed from a string such as
+    /// `"1.0"`.
+    pub fn category(&self, number: impl Into<Operands>) -> PluralCategory {
+        let operands = number.into();
+        self.rules
+            .iter()
+            .find(|(_, condition)| condition.matches(&operands))
+            .map_or(PluralCategory::Other, |(category, _)| *category)
+    }
+}
+
+/// Logs a locale CLDR has no plural rules for, once per locale. Its numbers
+/// are all `other`.
+fn warn_unknown_locale(locale: &str) {
+    static WARNED: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+    if WARNED
+        .lock()
+        .is_ok_and(|mut warned| warned.insert(locale.to_string()))
+    {
+        tracing::warn!(
+            "no CLDR plural rules for locale `{}`; every number is `other`",
+            locale
+        );
+    }
+}
+
+/// Whether CLDR has plural rules for `language`, e.g. `fil` but not `app`.
+pub(crate) fn has_rules(language: &str) -> bool {
+    [data::CARDINAL, data::ORDINAL].iter().any(|table| {
+        table
+            .binary_search_by(|(candidate, _)| (*candidate).cmp(language))
+            .is_ok()
+    })
+}
+
+/// Reports plural and selectordinal arguments in `node` that lack an option
+/// for a category `locale` uses. Arguments without `other` are already
+/// reported by the parser.
+pub fn check_categories(node: &AstNode, locale: &str) -> Vec<ParseError> {
+    let mut checker = Checker {
+        locale,
+        cardinal: PluralRules::cardinal(locale),
+        ordinal: PluralRules::ordinal(locale),
+        errors: Vec::new(),
+    };
+    checker.visit_node(node);
+    checker.errors
+}
+
+struct Checker<'l> {
+    locale: &'l str,
+    cardinal: PluralRules,
+    ordinal: PluralRules,
+    errors: Vec<ParseError>,
+}
+
+impl Checker<'_> {
+    fn check(&mut self, variable: &str, options: &Options, kind: PluralKind, span: Span) {
+        if !options.contains_key("other") {
+            return;
+        }
+        let rules = match kind {
+            PluralKind::Cardinal => &self.cardinal,
+            PluralKind::Ordinal => &self.ordinal,
+        };
+        let missing: Vec<_> = rules
+            .categories()
+            .into_iter()
+            .filter(|category| !options.contains_key(category.as_str()))
+            .map(|category| format!("`{}`", category))
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        self.errors.push(ParseError::new(
+            ParseErrorKind::MissingCategories {
+                variable: variable.to_string(),
+                kind: match kind {
+                    PluralKind::Cardinal => "plural",
+                    PluralKind::Ordinal => "selectordinal",
+                },
+                locale: self.locale.to_string(),
+                categories: missing.join(", "),
+            },
+            span,
+        ));
+    }
+}
+
+impl Visit for Checker<'_> {
+    fn visit_plural(&mut self, variable: &str, _offset: u32, options: &Options, span: Span) {
+        self.check(variable, options, PluralKind::Cardinal, span);
+        walk_options(self, options);
+    }
+
+    fn visit_select_ordinal(&mut self, variable: &str, options: &Options, span: Span) {
+        self.check(variable, options, PluralKind::Ordinal, span);
+        walk_options(self, options);
+    }
+}
+
+/// `relation and relation or relation ...`
+#[derive(Debug, Clone, Default)]
+struct Condition(Vec<Vec<Relation>>);
+
+impl Condition {
+    fn matches(&self, operands: &Operands) -> bool {
+        self.0
+            .iter()
+            .any(|relations| relations.iter().all(|relation| relation.matches(operands)))
+    }
+}
+
+/// `i % 10 = 2..4, 9` or `n != 1`.
+#[derive(Debug, Clone)]
+struct Relation {
+    operand: char,
+    modulus: Option<f64>,
+    negated: bool,
+    ranges: Vec<(f64, f64)>,
+}
+
+impl Relation {
+    fn matches(&self, operands: &Operands) -> bool {
+        let mut value = operands.get(self.operand);
+        if let Some(modulus) = self.modulus {
+            value %= modulus;
+        }
+        let found = value.fract() == 0.0
+            && self
+                .ranges
+                .iter()
+                .any(|&(low, high)| low <= value && value <= high);
+        found != self.negated
+    }
+}
+
+/// Parses `one: i = 1 and v = 0; few: ...` as written in the rule table.
+fn parse_rules(rules: &str) -> Vec<(PluralCategory, Condition)> {
+    rules
+        .split(';')
+        .filter_map(|rule| {
+            let (category, condition) = rule.split_once(':')?;
+            let category = PluralCategory::ALL
+                .into_iter()
+                .find(|candidate| candidate.as_str() == category.trim())?;
+            Some((category, parse_condition(condition)))
+        })
+        .collect()
+}
+
+fn parse_condition(condition: &str) -> Condition {
+    Condition(
+        condition
+            .split(" or ")
+            .map(|and| and.split(" and ").filter_map(parse_relation).collect())
+            .collect(),
+    )
+}
+
+fn parse_relation(relation: &str) -> Option<Relation> {
+    let (negated, (expression, ranges)) = match relation.split_once("!=") {
+        Some(parts) => (true, parts),
+        None => (false, relation.split_once('=')?),
+    };
+    let (operand, modulus) = match expression.split_once('%') {
+        Some((operand, modulus)) => (operand, Some(modulus.trim().parse().ok()?)),
+        None => (expression, None),
+    };
+    let ranges = ranges
+        .split(',')
+        .map(|range| {
+            let range = range.trim();
+            let (low, high) = range.split_once("..").unwrap_or((range, range));
+            Some((low.parse().ok()?, high.parse().ok()?))
+        })
+        .collect::<Option<_>>()?;
+    Some(Relation {
+        operand: operand.trim().chars().next()?,
+        modulus,
+        negated,
+        ranges,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use PluralCategory::*;
+
+    /// The category of each number, written as CLDR operands.
+    fn categories(rules: &PluralRules, numbers: &[&str]) -> Vec<PluralCategory> {
+        numbers
+            .iter()
+            .map(|number| rules.category(number.parse::<Operands>().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn english() {
+        let rules = PluralRules::cardinal("en-US");
+        assert_eq!(rules.categories(), [One, Other]);
+        assert_eq!(
+            categories(&rules, &["1", "0", "2", "1.0"]),
+            [One, Other, Other, Other]
+        );
+        let ordinal = PluralRules::ordinal("en");
+        assert_eq!(
+            categories(
+                &ordinal,
+                &["1", "2", "3", "4", "11", "12", "13", "21", "22", "23"]
+            ),
+            [One, Two, Few, Other, Other, Other, Other, One, Two, Few]
+        );
+    }
+
+    #[test]
+    fn french() {
+        let rules = PluralRules::cardinal("fr_CA");
+        assert_eq!(rules.categories(), [One, Many, Other]);
+        assert_eq!(
+            categories(
+                &rules,
+                &["0", "1", "1.5", "2", "1000000", "1c6", "1.2c6", "1c3"]
+            ),
+            [One, One, One, Other, Many, Many, Many, Other]
+        );
+    }
+
+    #[test]
+    fn russian() {
+        let rules = PluralRules::cardinal("ru");
+        assert_eq!(
+            categories(
+                &rules,
+                &["1", "21", "2", "24", "5", "11", "12", "111", "1.5"]
+            ),
+            [One, One, Few, Few, Many, Many, Many, Many, Other]
+        );
+    }
+
+    #[test]
+    fn arabic() {
+        let rules = PluralRules::cardinal("ar");
+        assert_eq!(rules.categories(), [Zero, One, Two, Few, Many, Other]);
+        assert_eq!(
+            categories(
+                &rules,
+                &["0", "1", "2", "3", "10", "11", "99", "100", "103"]
+            ),
+            [Zero, One, Two, Few, Few, Many, Many, Other, Few]
+        );
+    }
+
+    #[test]
+    fn polish() {
+        let rules = PluralRules::cardinal("pl");
+        assert_eq!(
+            categories(
+                &rules,
+                &["1", "2", "4", "22", "5", "12", "14", "21", "0", "1.5"]
+            ),
+            [One, Few, Few, Few, Many, Many, Many, Many, Many, Other]
+        );
+    }
+
+    #[test]
+    fn welsh() {
+        let rules = PluralRules::cardinal("cy");
+        assert_eq!(
+            categories(&rules, &["0", "1", "2", "3", "4", "6", "7"]),
+            [Zero, One, Two, Few, Other, Many, Other]
+        );
+        let ordinal = PluralRules::ordinal("cy");
+        assert_eq!(
+            categories(&ordinal, &["0", "1", "2", "3", "5", "7", "10"]),
+            [Zero, One, Two, Few, Many, Zero, Other]
+        );
+    }
+
+    #[test]
+    fn norwegian() {
+        for locale in ["nb", "nn", "no", "nb-NO"] {
+            let rules = PluralRules::cardinal(locale);
+            assert_eq!(rules.categories(), [One, Other], "{}", locale);
+            assert_eq!(categories(&rules, &["1", "2", "0"]), [One, Other, Other]);
+        }
+    }
+
+    #[test]
+    fn other_only_and_unknown_locales() {
+        assert_eq!(PluralRules::cardinal("ja").categories(), [Other]);
+        assert_eq!(PluralRules::cardinal("xx").categories(), [Other]);
+        assert!(has_rules("ja") && has_rules("kw") && !has_rules("xx"));
+    }
+
+    #[test]
+    fn reads_operands() {
+        let operands: Operands = "-1.50".parse().unwrap();
+        assert_eq!(
+            operands,
+            Operands {
+                n: 1.5,
+                i: 1,
+                v: 2,
+                w: 1,
+                f: 50,
+                t: 5,
+                e: 0
+            }
+        );
+        let operands: Operands = "1.25c1".parse().unwrap();
+        assert_eq!(
+            (operands.n, operands.i, operands.v, operands.f, operands.e),
+            (12.5, 12, 1, 5, 1)
+        );
+        assert!("1.2x".parse::<Operands>().is_err());
+    }
+}
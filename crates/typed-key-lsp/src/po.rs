@@ -0,0 +1,724 @@
+//! Gettext PO and POT catalogs.
+//!
+//! A PO file is a list of entries separated by blank lines:
+//!
+//! ```text
+//! # Shown on the cart page.
+//! msgctxt "cart"
+//! msgid "One item"
+//! msgid_plural "{count} items"
+//! msgstr[0] "Un article"
+//! msgstr[1] "{count} articles"
+//! ```
+//!
+//! The entry with an empty `msgid` is the header, whose `Plural-Forms` field
+//! says which `msgstr[n]` applies to a number.
+
+use crate::error::{PoError, PoErrorKind};
+use crate::plural::{PluralCategory, PluralRules};
+use crate::span::Span;
+
+/// A parsed PO or POT file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PoFile {
+    /// The header fields, e.g. `("Language", "fr")`.
+    pub header: Vec<(String, String)>,
+    /// Where the header entry is, if there is one.
+    pub header_span: Span,
+    pub entries: Vec<PoEntry>,
+}
+
+/// One message of a PO file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PoEntry {
+    pub context: Option<String>,
+    pub id: String,
+    pub plural_id: Option<String>,
+    /// The `msgstr`, or each `msgstr[n]` of a plural entry.
+    pub strings: Vec<String>,
+    /// Translator (`# `) and extracted (`#. `) comments, one per line.
+    pub comments: Vec<String>,
+    /// Flags from `#, ` lines, e.g. `fuzzy`.
+    pub flags: Vec<String>,
+    /// From the first line of the entry to the end of its last string.
+    pub span: Span,
+}
+
+impl PoEntry {
+    /// Whether the translation still needs review and is not used.
+    pub fn is_fuzzy(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "fuzzy")
+    }
+
+    /// Whether every `msgstr` is filled in.
+    pub fn is_translated(&self) -> bool {
+        !self.strings.is_empty() && self.strings.iter().all(|string| !string.is_empty())
+    }
+}
+
+/// More `msgstr[n]` than any language needs is a typo.
+const MAX_PLURALS: usize = 16;
+
+/// The field of an entry that a continuation line `"..."` extends.
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Context,
+    Id,
+    PluralId,
+    String(usize),
+}
+
+impl PoFile {
+    pub fn parse(content: &str) -> Result<PoFile, PoError> {
+        let mut file = PoFile::default();
+        let mut entry: Option<PoEntry> = None;
+        let mut field = None;
+        let mut offset = 0;
+
+        for line in content.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let line = line.trim_end_matches(['\n', '\r']);
+            let trimmed = line.trim_start();
+            let column = start + line.len() - trimmed.len();
+            let line_span = Span::new(column, start + line.len());
+
+            if trimmed.is_empty() {
+                file.finish(entry.take())?;
+                field = None;
+                continue;
+            }
+
+            if let Some(comment) = trimmed.strip_prefix('#') {
+                // A comment after the strings belongs to the next entry.
+                if entry
+                    .as_ref()
+                    .is_some_and(|entry| !entry.strings.is_empty())
+                {
+                    file.finish(entry.take())?;
+                }
+                field = None;
+                if comment.starts_with('~') {
+                    // Obsolete entries are kept by gettext but never used.
+                    continue;
+                }
+                let current = entry.get_or_insert_with(|| PoEntry {
+                    span: line_span,
+                    ..PoEntry::default()
+                });
+                if let Some(flags) = comment.strip_prefix(',') {
+                    current
+                        .flags
+                        .extend(flags.split(',').map(|flag| flag.trim().to_string()));
+                } else if let Some(extracted) = comment.strip_prefix('.') {
+                    current.comments.push(extracted.trim().to_string());
+                } else if comment.is_empty() || comment.starts_with(' ') {
+                    current.comments.push(comment.trim().to_string());
+                }
+                // `#:` references and `#|` previous strings are not kept.
+                continue;
+            }
+
+            if trimmed.starts_with('"') {
+                let (Some(current), Some(field)) = (entry.as_mut(), field) else {
+                    return Err(PoError::new(PoErrorKind::UnexpectedLine, line_span));
+                };
+                let value = parse_string(trimmed, column)?;
+                current.span.end = line_span.end;
+                match field {
+                    Field::Context => current.context.get_or_insert_default().push_str(&value),
+                    Field::Id => current.id.push_str(&value),
+                    Field::PluralId => current.plural_id.get_or_insert_default().push_str(&value),
+                    Field::String(index) => current.strings[index].push_str(&value),
+                }
+                continue;
+            }
+
+            let keyword_end = trimmed
+                .find(|c: char| c.is_whitespace())
+                .unwrap_or(trimmed.len());
+            let (keyword, rest) = trimmed.split_at(keyword_end);
+            let rest = rest.trim_start();
+            let rest_column = start + line.len() - rest.len();
+            let next = match keyword {
+                "msgctxt" => Field::Context,
+                "msgid" => Field::Id,
+                "msgid_plural" => Field::PluralId,
+                "msgstr" => Field::String(0),
+                _ => match keyword
+                    .strip_prefix("msgstr[")
+                    .and_then(|index| index.strip_suffix(']'))
+                    .and_then(|index| index.parse().ok())
+                    .filter(|index| *index < MAX_PLURALS)
+                {
+                    Some(index) => Field::String(index),
+                    None => return Err(PoError::new(PoErrorKind::UnexpectedLine, line_span)),
+                },
+            };
+            // Entries need not be separated by blank lines.
+            if matches!(next, Field::Context | Field::Id)
+                && entry
+                    .as_ref()
+                    .is_some_and(|entry| !entry.strings.is_empty())
+            {
+                file.finish(entry.take())?;
+            }
+            let value = parse_string(rest, rest_column)?;
+            let current = entry.get_or_insert_with(|| PoEntry {
+                span: line_span,
+                ..PoEntry::default()
+            });
+            current.span.end = line_span.end;
+            match next {
+                Field::Context => current.context = Some(value),
+                Field::Id => current.id = value,
+                Field::PluralId => current.plural_id = Some(value),
+                Field::String(index) => {
+                    if current.strings.len() <= index {
+                        current.strings.resize(index + 1, String::new());
+                    }
+                    current.strings[index] = value;
+                }
+            }
+            field = Some(next);
+        }
+        file.finish(entry)?;
+        Ok(file)
+    }
+
+    /// The value of a header field such as `Language`.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.header
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The `Plural-Forms` of the header, or gettext's default of two forms,
+    /// singular for 1 and plural otherwise.
+    pub fn plural_forms(&self) -> Result<PluralForms, PoError> {
+        match self.header("Plural-Forms") {
+            Some(forms) => PluralForms::parse(forms).map_err(|error| PoError {
+                span: self.header_span,
+                ..error
+            }),
+            None => Ok(PluralForms::default()),
+        }
+    }
+
+    fn finish(&mut self, entry: Option<PoEntry>) -> Result<(), PoError> {
+        let Some(entry) = entry else {
+            return Ok(());
+        };
+        if entry.strings.is_empty() {
+            // A block of comments only, e.g. at the top of the file.
+            if entry.id.is_empty() && entry.context.is_none() {
+                return Ok(());
+            }
+            return Err(PoError::new(PoErrorKind::MissingMsgstr, entry.span));
+        }
+        if entry.id.is_empty() && entry.context.is_none() && self.header.is_empty() {
+            self.header = entry.strings[0]
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                .collect();
+            self.header_span = entry.span;
+            return Ok(());
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+}
+
+/// Reads a quoted string starting at byte `start` of the file.
+fn parse_string(text: &str, start: usize) -> Result<String, PoError> {
+    let unterminated = || {
+        PoError::new(
+            PoErrorKind::UnterminatedString,
+            Span::new(start, start + text.len()),
+        )
+    };
+    let body = text.strip_prefix('"').ok_or_else(unterminated)?;
+    let mut value = String::new();
+    let mut chars = body.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => {
+                if !body[index + 1..].trim().is_empty() {
+                    return Err(PoError::new(
+                        PoErrorKind::UnexpectedLine,
+                        Span::new(start, start + text.len()),
+                    ));
+                }
+                return Ok(value);
+            }
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 'a')) => value.push('\u{7}'),
+                Some((_, 'b')) => value.push('\u{8}'),
+                Some((_, 'f')) => value.push('\u{c}'),
+                Some((_, 'v')) => value.push('\u{b}'),
+                Some((_, escaped)) => value.push(escaped),
+                None => return Err(unterminated()),
+            },
+            c => value.push(c),
+        }
+    }
+    Err(unterminated())
+}
+
+/// The `Plural-Forms` header, e.g. `nplurals=2; plural=(n != 1);`: how many
+/// plural strings an entry has and which one a number uses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluralForms {
+    pub count: usize,
+    expression: Expression,
+}
+
+impl Default for PluralForms {
+    fn default() -> Self {
+        PluralForms {
+            count: 2,
+            expression: Expression::Binary(
+                Operator::NotEqual,
+                Box::new(Expression::N),
+                Box::new(Expression::Number(1)),
+            ),
+        }
+    }
+}
+
+impl PluralForms {
+    pub fn parse(forms: &str) -> Result<PluralForms, PoError> {
+        let invalid = || {
+            PoError::new(
+                PoErrorKind::InvalidPluralForms {
+                    forms: forms.to_string(),
+                },
+                Span::default(),
+            )
+        };
+        let mut count = None;
+        let mut expression = None;
+        for part in forms.split(';') {
+            let Some((name, value)) = part.split_once('=') else {
+                continue;
+            };
+            match name.trim() {
+                "nplurals" => count = value.trim().parse::<usize>().ok(),
+                "plural" => expression = Some(value.trim()),
+                _ => {}
+            }
+        }
+        let count = count.filter(|count| *count > 0).ok_or_else(invalid)?;
+        let mut parser = ExpressionParser {
+            tokens: tokenize(expression.ok_or_else(invalid)?).ok_or_else(invalid)?,
+            position: 0,
+            depth: 0,
+        };
+        let expression = parser.conditional().map_err(|kind| match kind {
+            Some(kind) => PoError::new(kind, Span::default()),
+            None => invalid(),
+        })?;
+        if parser.position != parser.tokens.len() {
+            return Err(invalid());
+        }
+        Ok(PluralForms { count, expression })
+    }
+
+    /// The index of the `msgstr[n]` used for `n`.
+    pub fn index(&self, n: u64) -> usize {
+        let index = self.expression.evaluate(n);
+        usize::try_from(index)
+            .unwrap_or(usize::MAX)
+            .min(self.count - 1)
+    }
+
+    /// Which `msgstr[n]` each CLDR category of `locale` reads, in CLDR
+    /// order. A category reads the first form whose smallest number is in
+    /// it; categories gettext does not tell apart, such as `many` in French,
+    /// read the last form.
+    pub fn categories(&self, locale: &str) -> Vec<(PluralCategory, usize)> {
+        let rules = PluralRules::cardinal(locale);
+        let first_categories: Vec<_> = (0..self.count)
+            .map(|index| {
+                (0..1000u64)
+                    .find(|n| self.index(*n) == index)
+                    .map(|n| rules.category(n as f64))
+            })
+            .collect();
+        rules
+            .categories()
+            .into_iter()
+            .map(|category| {
+                let index = first_categories
+                    .iter()
+                    .position(|first| *first == Some(category))
+                    .unwrap_or(self.count - 1);
+                (category, index)
+            })
+            .collect()
+    }
+}
+
+/// A C expression of `n`, as gettext writes plural formulas.
+#[derive(Debug, Clone, PartialEq)]
+enum Expression {
+    N,
+    Number(u64),
+    Not(Box<Expression>),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+impl Operator {
+    /// Binding strength, loosest first, as in C.
+    fn precedence(self) -> u8 {
+        match self {
+            Operator::Or => 1,
+            Operator::And => 2,
+            Operator::Equal | Operator::NotEqual => 3,
+            Operator::Less | Operator::LessEqual | Operator::Greater | Operator::GreaterEqual => 4,
+            Operator::Add | Operator::Subtract => 5,
+            Operator::Multiply | Operator::Divide | Operator::Remainder => 6,
+        }
+    }
+
+    fn apply(self, left: u64, right: u64) -> u64 {
+        match self {
+            Operator::Or => u64::from(left != 0 || right != 0),
+            Operator::And => u64::from(left != 0 && right != 0),
+            Operator::Equal => u64::from(left == right),
+            Operator::NotEqual => u64::from(left != right),
+            Operator::Less => u64::from(left < right),
+            Operator::LessEqual => u64::from(left <= right),
+            Operator::Greater => u64::from(left > right),
+            Operator::GreaterEqual => u64::from(left >= right),
+            Operator::Add => left.wrapping_add(right),
+            Operator::Subtract => left.wrapping_sub(right),
+            Operator::Multiply => left.wrapping_mul(right),
+            Operator::Divide => left.checked_div(right).unwrap_or_default(),
+            Operator::Remainder => left.checked_rem(right).unwrap_or_default(),
+        }
+    }
+}
+
+impl Expression {
+    fn evaluate(&self, n: u64) -> u64 {
+        match self {
+            Expression::N => n,
+            Expression::Number(number) => *number,
+            Expression::Not(operand) => u64::from(operand.evaluate(n) == 0),
+            Expression::Binary(operator, left, right) => {
+                operator.apply(left.evaluate(n), right.evaluate(n))
+            }
+            Expression::Conditional(condition, then, otherwise) => {
+                if condition.evaluate(n) != 0 {
+                    then.evaluate(n)
+                } else {
+                    otherwise.evaluate(n)
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    N,
+    Number(u64),
+    Operator(Operator),
+    Not,
+    Question,
+    Colon,
+    Open,
+    Close,
+}
+
+fn tokenize(expression: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut rest = expression.trim_start();
+    while let Some(c) = rest.chars().next() {
+        let (token, length) = if c.is_ascii_digit() {
+            let length = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            (Token::Number(rest[..length].parse().ok()?), length)
+        } else {
+            let two = rest.get(..2).unwrap_or_default();
+            match two {
+                "||" => (Token::Operator(Operator::Or), 2),
+                "&&" => (Token::Operator(Operator::And), 2),
+                "==" => (Token::Operator(Operator::Equal), 2),
+                "!=" => (Token::Operator(Operator::NotEqual), 2),
+                "<=" => (Token::Operator(Operator::LessEqual), 2),
+                ">=" => (Token::Operator(Operator::GreaterEqual), 2),
+                _ => {
+                    let token = match c {
+                        'n' => Token::N,
+                        '<' => Token::Operator(Operator::Less),
+                        '>' => Token::Operator(Operator::Greater),
+                        '+' => Token::Operator(Operator::Add),
+                        '-' => Token::Operator(Operator::Subtract),
+                        '*' => Token::Operator(Operator::Multiply),
+                        '/' => Token::Operator(Operator::Divide),
+                        '%' => Token::Operator(Operator::Remainder),
+                        '!' => Token::Not,
+                        '?' => Token::Question,
+                        ':' => Token::Colon,
+                        '(' => Token::Open,
+                        ')' => Token::Close,
+                        _ => return None,
+                    };
+                    (token, 1)
+                }
+            }
+        };
+        tokens.push(token);
+        rest = rest[length..].trim_start();
+    }
+    Some(tokens)
+}
+
+/// How deep parentheses, operators, `?:` and `!` may nest in a `Plural-Forms`
+/// formula, so a broken header cannot overflow the stack.
+pub const MAX_DEPTH: usize = 128;
+
+/// Fails with `None` for a formula it cannot read, or the kind of a more
+/// specific error.
+type Parsed = Result<Expression, Option<PoErrorKind>>;
+
+struct ExpressionParser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// Conditionals, operators and `!` open around the current token.
+    depth: usize,
+}
+
+impl ExpressionParser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek()?;
+        self.position += 1;
+        Some(token)
+    }
+
+    /// `condition ? then : otherwise`, which groups to the right.
+    fn conditional(&mut self) -> Parsed {
+        self.nested(|parser| {
+            let condition = parser.binary(1)?;
+            if parser.peek() != Some(Token::Question) {
+                return Ok(condition);
+            }
+            parser.position += 1;
+            let then = parser.conditional()?;
+            if parser.next().ok_or(None)? != Token::Colon {
+                return Err(None);
+            }
+            let otherwise = parser.conditional()?;
+            Ok(Expression::Conditional(
+                Box::new(condition),
+                Box::new(then),
+                Box::new(otherwise),
+            ))
+        })
+    }
+
+    /// Operators that bind at least as tightly as `precedence`. Each one
+    /// nests the expression before it a level deeper.
+    fn binary(&mut self, precedence: u8) -> Parsed {
+        let depth = self.depth;
+        let mut left = self.unary()?;
+        while let Some(Token::Operator(operator)) = self.peek() {
+            if operator.precedence() < precedence {
+                break;
+            }
+            self.position += 1;
+            self.deeper()?;
+            let right = self.binary(operator.precedence() + 1)?;
+            left = Expression::Binary(operator, Box::new(left), Box::new(right));
+        }
+        self.depth = depth;
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Parsed {
+        match self.next().ok_or(None)? {
+            Token::N => Ok(Expression::N),
+            Token::Number(number) => Ok(Expression::Number(number)),
+            Token::Not => {
+                let operand = self.nested(Self::unary)?;
+                Ok(Expression::Not(Box::new(operand)))
+            }
+            Token::Open => {
+                let inner = self.conditional()?;
+                if self.next().ok_or(None)? != Token::Close {
+                    return Err(None);
+                }
+                Ok(inner)
+            }
+            _ => Err(None),
+        }
+    }
+
+    /// Parses with `parse` one level deeper.
+    fn nested(&mut self, parse: impl FnOnce(&mut Self) -> Parsed) -> Parsed {
+        self.deeper()?;
+        let expression = parse(self);
+        self.depth -= 1;
+        expression
+    }
+
+    /// Goes one level deeper, or fails past `MAX_DEPTH`.
+    fn deeper(&mut self) -> Result<(), Option<PoErrorKind>> {
+        if self.depth == MAX_DEPTH {
+            return Err(Some(PoErrorKind::PluralFormsTooDeep { limit: MAX_DEPTH }));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLISH: &str =
+        "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) ? 1 : 2);";
+
+    #[test]
+    fn reads_contexts_comments_and_flags() {
+        let file = PoFile::parse(concat!(
+            "# Keep it short.\n",
+            "#. Shown on the cart page.\n",
+            "#, fuzzy, c-format\n",
+            "msgctxt \"button\"\n",
+            "msgid \"Save\"\n",
+            "msgstr \"Enregistrer\"\n",
+        ))
+        .unwrap();
+        let entry = &file.entries[0];
+        assert_eq!(entry.context.as_deref(), Some("button"));
+        assert_eq!(entry.id, "Save");
+        assert_eq!(entry.strings, ["Enregistrer"]);
+        assert_eq!(
+            entry.comments,
+            ["Keep it short.", "Shown on the cart page."]
+        );
+        assert!(entry.is_fuzzy());
+    }
+
+    #[test]
+    fn reads_plural_forms() {
+        let file = PoFile::parse(&format!(
+            "msgid \"\"\nmsgstr \"Language: pl\\n\"\n\"Plural-Forms: {}\\n\"\n\n\
+             msgid \"item\"\nmsgid_plural \"items\"\n\
+             msgstr[0] \"element\"\nmsgstr[1] \"elementy\"\nmsgstr[2] \"elementów\"\n",
+            POLISH
+        ))
+        .unwrap();
+        assert_eq!(file.header("Language"), Some("pl"));
+        let entry = &file.entries[0];
+        assert_eq!(entry.plural_id.as_deref(), Some("items"));
+        assert_eq!(entry.strings, ["element", "elementy", "elementów"]);
+        assert_eq!(file.plural_forms().unwrap().count, 3);
+    }
+
+    #[test]
+    fn evaluates_plural_expressions() {
+        let polish = PluralForms::parse(POLISH).unwrap();
+        let indices: Vec<_> = [1, 2, 4, 5, 12, 22, 25, 0].map(|n| polish.index(n)).into();
+        assert_eq!(indices, [0, 1, 1, 2, 2, 1, 2, 2]);
+        assert_eq!(
+            polish.categories("pl"),
+            [
+                (PluralCategory::One, 0),
+                (PluralCategory::Few, 1),
+                (PluralCategory::Many, 2),
+                (PluralCategory::Other, 2),
+            ]
+        );
+
+        let french = PluralForms::parse("nplurals=2; plural=(n > 1);").unwrap();
+        assert_eq!(
+            (french.index(0), french.index(1), french.index(2)),
+            (0, 0, 1)
+        );
+        assert_eq!(
+            french.categories("fr"),
+            [
+                (PluralCategory::One, 0),
+                (PluralCategory::Many, 1),
+                (PluralCategory::Other, 1),
+            ]
+        );
+
+        // An index past the last form reads the last one.
+        let wide = PluralForms::parse("nplurals=2; plural=n;").unwrap();
+        assert_eq!(wide.index(7), 1);
+        assert!(PluralForms::parse("nplurals=2; plural=n +;").is_err());
+        assert!(PluralForms::parse("plural=n != 1;").is_err());
+    }
+
+    #[test]
+    fn unescapes_strings() {
+        let file = PoFile::parse(concat!(
+            "msgid \"Say \\\"hi\\\"\\n\"\n",
+            "msgstr \"\"\n",
+            "\"Dis \\\"salut\\\"\\t\"\n",
+            "\"\\\\ fin\"\n",
+        ))
+        .unwrap();
+        let entry = &file.entries[0];
+        assert_eq!(entry.id, "Say \"hi\"\n");
+        assert_eq!(entry.strings, ["Dis \"salut\"\t\\ fin"]);
+        assert!(PoFile::parse("msgid \"open\n").is_err());
+    }
+
+    #[test]
+    fn limits_plural_expression_depth() {
+        let nested = format!(
+            "nplurals=2; plural={}n{};",
+            "(".repeat(100),
+            ")".repeat(100)
+        );
+        assert_eq!(PluralForms::parse(&nested).unwrap().index(1), 1);
+
+        for formula in [
+            format!("{}n", "(".repeat(200_000)),
+            format!("{}n", "!".repeat(200_000)),
+            format!("n{}", "+n".repeat(200_000)),
+            format!("{}0", "n ? 1 : ".repeat(200_000)),
+        ] {
+            let error =
+                PluralForms::parse(&format!("nplurals=2; plural={};", formula)).unwrap_err();
+            assert_eq!(
+                error.kind,
+                PoErrorKind::PluralFormsTooDeep { limit: MAX_DEPTH }
+            );
+        }
+    }
+}
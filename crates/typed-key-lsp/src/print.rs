@@ -0,0 +1,433 @@
+use std::cmp::Ordering;
+
+use crate::dialect::{Dialect, Formatter};
+use crate::lex::starts_quote;
+use crate::parse::{AstNode, Options, PLURAL_CATEGORIES};
+use crate::span::Span;
+use crate::visit::Visit;
+
+/// Turns a parsed message back into text.
+///
+/// `print` keeps options in source order, and its output parses back to the
+/// same tree apart from spans. `pretty` gives the canonical form instead:
+/// exact matches first, then CLDR categories, with `other` last, and every
+/// option of a choice argument on its own line.
+pub struct Printer {
+    dialect: Dialect,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self::with_dialect(Dialect::Icu)
+    }
+
+    /// Prints text escaped for `dialect`. `Dialect::Auto` means i18next if the
+    /// tree contains interpolations, ICU otherwise; Fluent trees are printed
+    /// as ICU.
+    pub fn with_dialect(dialect: Dialect) -> Self {
+        Printer { dialect }
+    }
+
+    pub fn print(&self, node: &AstNode) -> String {
+        self.write(node, false)
+    }
+
+    pub fn pretty(&self, node: &AstNode) -> String {
+        self.write(node, true)
+    }
+
+    fn write(&self, node: &AstNode, pretty: bool) -> String {
+        let dialect = match self.dialect {
+            Dialect::Auto if contains_interpolation(node) => Dialect::I18next,
+            Dialect::Auto | Dialect::Fluent => Dialect::Icu,
+            dialect => dialect,
+        };
+        let mut writer = Writer {
+            out: String::new(),
+            dialect,
+            pretty,
+            indent: 0,
+        };
+        writer.node(node, false);
+        writer.out
+    }
+}
+
+impl Default for Printer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Writer {
+    out: String,
+    dialect: Dialect,
+    pretty: bool,
+    indent: usize,
+}
+
+impl Writer {
+    fn nodes(&mut self, nodes: &[AstNode], in_plural: bool) {
+        for node in nodes {
+            self.node(node, in_plural);
+        }
+    }
+
+    fn node(&mut self, node: &AstNode, in_plural: bool) {
+        match node {
+            AstNode::Root(children, _) => self.nodes(children, in_plural),
+            AstNode::Text(text, _) => self.text(text, in_plural),
+            AstNode::Variable(variable, _) => {
+                self.out.push('{');
+                self.out.push_str(variable);
+                self.out.push('}');
+            }
+            AstNode::Pound(_) => self.out.push('#'),
+            AstNode::Plural {
+                variable,
+                offset,
+                options,
+                ..
+            } => {
+                self.out.push('{');
+                self.out.push_str(variable);
+                self.out.push_str(", plural,");
+                if *offset > 0 {
+                    self.out.push_str(&format!(" offset:{}", offset));
+                }
+                self.options(options, Some(&PLURAL_CATEGORIES), true);
+                self.out.push('}');
+            }
+            AstNode::SelectOrdinal {
+                variable, options, ..
+            } => {
+                self.out.push('{');
+                self.out.push_str(variable);
+                self.out.push_str(", selectordinal,");
+                self.options(options, Some(&PLURAL_CATEGORIES), true);
+                self.out.push('}');
+            }
+            AstNode::Select {
+                variable, options, ..
+            } => {
+                self.out.push('{');
+                self.out.push_str(variable);
+                self.out.push_str(", select,");
+                self.options(options, None, false);
+                self.out.push('}');
+            }
+            AstNode::Number {
+                variable, style, ..
+            } => self.simple_argument(variable, "number", style),
+            AstNode::Date {
+                variable, style, ..
+            } => self.simple_argument(variable, "date", style),
+            AstNode::Time {
+                variable, style, ..
+            } => self.simple_argument(variable, "time", style),
+            AstNode::HtmlTag {
+                name,
+                attributes,
+                self_closing,
+                children,
+                ..
+            } => {
+                self.out.push('<');
+                self.out.push_str(name);
+                for attribute in attributes {
+                    self.out.push(' ');
+                    self.out.push_str(&attribute.name);
+                    if let Some(value) = &attribute.value {
+                        let quote = if value.contains('"') { '\'' } else { '"' };
+                        self.out.push('=');
+                        self.out.push(quote);
+                        self.out.push_str(value);
+                        self.out.push(quote);
+                    }
+                }
+                if *self_closing && children.is_empty() {
+                    self.out.push_str("/>");
+                } else {
+                    self.out.push('>');
+                    self.nodes(children, in_plural);
+                    self.out.push_str("</");
+                    self.out.push_str(name);
+                    self.out.push('>');
+                }
+            }
+            AstNode::Interpolation {
+                variable,
+                formatters,
+                escaped,
+                ..
+            } => {
+                self.out.push_str(if *escaped { "{{" } else { "{{- " });
+                self.out.push_str(variable);
+                for formatter in formatters {
+                    self.out.push_str(", ");
+                    self.out.push_str(&formatter.name);
+                    if let Some(options) = &formatter.options {
+                        self.out.push('(');
+                        self.out.push_str(options);
+                        self.out.push(')');
+                    }
+                }
+                self.out.push_str("}}");
+            }
+            // Broken parts print as whatever the parser made of them.
+            AstNode::Error { recovered, .. } => {
+                if let Some(node) = recovered {
+                    self.node(node, in_plural);
+                }
+            }
+        }
+    }
+
+    fn simple_argument(&mut self, variable: &str, kind: &str, style: &Option<String>) {
+        self.out.push('{');
+        self.out.push_str(variable);
+        self.out.push_str(", ");
+        self.out.push_str(kind);
+        if let Some(style) = style {
+            self.out.push_str(", ");
+            self.out.push_str(style);
+        }
+        self.out.push('}');
+    }
+
+    fn options(&mut self, options: &Options, categories: Option<&[&str]>, in_plural: bool) {
+        let mut options: Vec<_> = options.iter().collect();
+        if self.pretty {
+            options.sort_by(|(a, _), (b, _)| compare_options(a, b, categories));
+            self.indent += 1;
+        }
+
+        for (key, body) in options {
+            if self.pretty {
+                self.newline();
+            } else {
+                self.out.push(' ');
+            }
+            self.out.push_str(key);
+            self.out.push_str(" {");
+            self.nodes(body, in_plural);
+            self.out.push('}');
+        }
+
+        if self.pretty {
+            self.indent -= 1;
+            self.newline();
+        }
+    }
+
+    fn newline(&mut self) {
+        self.out.push('\n');
+        self.out.push_str(&"  ".repeat(self.indent));
+    }
+
+    /// Writes text, quoting whatever the lexer would otherwise read as syntax.
+    /// i18next has no escapes, so its text is written as is.
+    fn text(&mut self, text: &str, in_plural: bool) {
+        if self.dialect != Dialect::Icu {
+            self.out.push_str(text);
+            return;
+        }
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let next = chars.get(i + 1).copied();
+            if is_syntax(c, next, in_plural) {
+                // Quote the whole run, with the apostrophes that follow it: a
+                // `''` right after the closing quote would be read as part of it.
+                self.out.push('\'');
+                while let Some(&c) = chars.get(i) {
+                    if c == '\'' {
+                        self.out.push_str("''");
+                    } else if is_syntax(c, chars.get(i + 1).copied(), in_plural) {
+                        self.out.push(c);
+                    } else {
+                        break;
+                    }
+                    i += 1;
+                }
+                self.out.push('\'');
+                continue;
+            }
+            // The next node may start with syntax, so a trailing apostrophe is
+            // doubled too.
+            if c == '\'' && next.is_none_or(|next| next == '\'' || starts_quote(next, in_plural)) {
+                self.out.push_str("''");
+            } else {
+                self.out.push(c);
+            }
+            i += 1;
+        }
+    }
+}
+
+/// Characters in text that the lexer would treat as syntax.
+fn is_syntax(c: char, next: Option<char>, in_plural: bool) -> bool {
+    match c {
+        '{' | '}' => true,
+        '#' => in_plural,
+        '<' => next.is_some_and(|next| next.is_alphanumeric() || next == '/'),
+        _ => false,
+    }
+}
+
+/// Canonical option order: exact matches by value, then CLDR categories,
+/// then anything else, with `other` last.
+fn compare_options(a: &str, b: &str, categories: Option<&[&str]>) -> Ordering {
+    option_group(a, categories)
+        .cmp(&option_group(b, categories))
+        .then_with(|| {
+            exact_value(a)
+                .partial_cmp(&exact_value(b))
+                .unwrap_or(Ordering::Equal)
+        })
+}
+
+fn option_group(key: &str, categories: Option<&[&str]>) -> (u8, usize) {
+    if key == "other" {
+        (3, 0)
+    } else if exact_value(key).is_some() {
+        (0, 0)
+    } else if let Some(index) =
+        categories.and_then(|categories| categories.iter().position(|c| *c == key))
+    {
+        (1, index)
+    } else {
+        (2, 0)
+    }
+}
+
+fn exact_value(key: &str) -> Option<f64> {
+    key.strip_prefix('=')?.parse().ok()
+}
+
+fn contains_interpolation(node: &AstNode) -> bool {
+    #[derive(Default)]
+    struct Finder(bool);
+
+    impl Visit for Finder {
+        fn visit_interpolation(&mut self, _: &str, _: &[Formatter], _: bool, _: Span) {
+            self.0 = true;
+        }
+    }
+
+    let mut finder = Finder::default();
+    finder.visit_node(node);
+    finder.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Parser;
+    use crate::schema::catalog_to_json;
+    use serde_json::Value;
+
+    /// The tree as JSON without its spans, which printing does not keep.
+    fn unspanned(node: &AstNode) -> Value {
+        fn strip(value: &mut Value) {
+            match value {
+                Value::Object(map) => {
+                    map.shift_remove("span");
+                    map.values_mut().for_each(strip);
+                }
+                Value::Array(items) => items.iter_mut().for_each(strip),
+                _ => {}
+            }
+        }
+        let key = String::new();
+        let mut json = catalog_to_json([(&key, node)]);
+        strip(&mut json);
+        json
+    }
+
+    fn parse(message: &str) -> AstNode {
+        Parser::new(message)
+            .parse()
+            .unwrap_or_else(|error| panic!("{:?} does not parse: {}", message, error))
+    }
+
+    fn assert_round_trip(message: &str) {
+        let tree = parse(message);
+        let printed = Printer::new().print(&tree);
+        assert_eq!(
+            unspanned(&parse(&printed)),
+            unspanned(&tree),
+            "{:?} printed as {:?}",
+            message,
+            printed
+        );
+        // The canonical form only reorders options, so it is stable.
+        let pretty = Printer::new().pretty(&tree);
+        assert_eq!(
+            Printer::new().pretty(&parse(&pretty)),
+            pretty,
+            "{:?} pretty-printed as {:?}",
+            message,
+            pretty
+        );
+    }
+
+    #[test]
+    fn round_trips_arguments() {
+        assert_round_trip("Hello {name}, you have {count, number} items");
+        assert_round_trip("{when, date, short} at {when, time}");
+        assert_round_trip("{price, number, ::currency/EUR}");
+        assert_round_trip("{gender, select, male {He} female {She} other {They}}");
+        assert_round_trip("{n, plural, offset:1 =0 {none} one {# item} other {# items}}");
+        assert_round_trip("{pos, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}");
+    }
+
+    #[test]
+    fn round_trips_apostrophes() {
+        assert_round_trip("It's {name}'s turn");
+        assert_round_trip("Quote '{name}' literally");
+        assert_round_trip("Two quotes: ''");
+        assert_round_trip("'{' and '}' and '#'");
+    }
+
+    #[test]
+    fn round_trips_pound() {
+        // `#` is the count only inside a plural option.
+        assert_round_trip("Item #1 of {n, plural, one {# item} other {# items}}");
+        assert_round_trip("{n, plural, other {'#' is # here}}");
+        assert_round_trip("{g, select, other {# stays text}}");
+    }
+
+    #[test]
+    fn round_trips_angle_brackets() {
+        assert_round_trip("Press <b>{key}</b> and 1 < 2");
+        assert_round_trip("<a href=\"/terms\">terms</a> or <br/>");
+        assert_round_trip("a <0>numbered</0> tag");
+        assert_round_trip("less than '<'b");
+    }
+
+    #[test]
+    fn escapes_text_that_would_read_as_syntax() {
+        let tree = AstNode::Root(
+            vec![AstNode::Text("{x} <b> ' #".to_string(), Span::default())],
+            Span::default(),
+        );
+        let printed = Printer::new().print(&tree);
+        assert_eq!(unspanned(&parse(&printed)), unspanned(&tree));
+    }
+
+    #[test]
+    fn pretty_orders_options_canonically() {
+        let tree = parse("{n, plural, other {many} one {one} =0 {none} few {few} =1 {exactly}}");
+        assert_eq!(
+            Printer::new().pretty(&tree),
+            "{n, plural,\n  =0 {none}\n  =1 {exactly}\n  one {one}\n  few {few}\n  other {many}\n}"
+        );
+        assert_eq!(
+            Printer::new().print(&tree),
+            "{n, plural, other {many} one {one} =0 {none} few {few} =1 {exactly}}"
+        );
+    }
+}
@@ -0,0 +1,549 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+
+use crate::error::{RenderError, RenderErrorKind};
+use crate::parse::{AstNode, Attribute, Options};
+use crate::plural::{PluralCategory, PluralRules};
+use crate::span::Span;
+
+/// A value passed to a message argument.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    /// The fields of an i18next `{{user.name}}` argument.
+    Object(IndexMap<String, Value>),
+}
+
+impl Value {
+    /// The value as a finite number; a string is read as one, so that
+    /// `--arg count=3` can select a plural option.
+    pub fn as_number(&self) -> Option<f64> {
+        let number = match self {
+            Value::Number(number) => *number,
+            Value::String(string) => string.trim().parse().ok()?,
+            Value::Object(_) => return None,
+        };
+        number.is_finite().then_some(number)
+    }
+
+    /// The value at a dotted `path` below this one, e.g. `name` in `user`.
+    fn field(&self, path: &str) -> Option<&Value> {
+        path.split('.').try_fold(self, |value, field| match value {
+            Value::Object(fields) => fields.get(field),
+            _ => None,
+        })
+    }
+}
+
+/// Sets the argument at a dotted `path`, so that `user.name` is the `name`
+/// field of the `user` argument.
+pub fn insert_argument(arguments: &mut IndexMap<String, Value>, path: &str, value: Value) {
+    let (root, rest) = match path.split_once('.') {
+        Some((root, rest)) => (root, Some(rest)),
+        None => (path, None),
+    };
+    let Some(rest) = rest else {
+        arguments.insert(root.to_string(), value);
+        return;
+    };
+    let entry = arguments
+        .entry(root.to_string())
+        .or_insert_with(|| Value::Object(IndexMap::new()));
+    if !matches!(entry, Value::Object(_)) {
+        *entry = Value::Object(IndexMap::new());
+    }
+    if let Value::Object(fields) = entry {
+        insert_argument(fields, rest, value);
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Number(number) => f.write_str(&format_number(*number, None)),
+            Value::String(string) => f.write_str(string),
+            Value::Object(fields) => {
+                f.write_str("{")?;
+                for (index, (name, value)) in fields.iter().enumerate() {
+                    let separator = if index == 0 { "" } else { ", " };
+                    write!(f, "{}{}: {}", separator, name, value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(number: f64) -> Self {
+        Value::Number(number)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(number: i64) -> Self {
+        Value::Number(number as f64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::String(string.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::String(string)
+    }
+}
+
+/// What to do with the tags of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tags {
+    /// Write tags as they are in the message, e.g. for HTML output.
+    #[default]
+    Keep,
+    /// Write only the content of tags, e.g. for plain-text previews.
+    Strip,
+}
+
+/// Formats a parsed message with argument values.
+///
+/// Plural and selectordinal options are picked by exact match first, then by
+/// the CLDR plural category of the number in the renderer's locale, falling
+/// back to `other`. They and `number` arguments read a string value as a
+/// number and refuse values that aren't finite numbers. Numbers are written
+/// without grouping and with at most three fraction digits. Dates and times
+/// aren't formatted: they fail with `RenderErrorKind::Unsupported`. A dotted
+/// i18next `{{user.name}}` reads the `name` field of the `user` argument, the
+/// one `arguments` reports.
+pub struct Renderer {
+    cardinal: PluralRules,
+    ordinal: PluralRules,
+    tags: Tags,
+}
+
+impl Renderer {
+    pub fn new(locale: &str) -> Self {
+        Self::with_tags(locale, Tags::default())
+    }
+
+    pub fn with_tags(locale: &str, tags: Tags) -> Self {
+        Renderer {
+            cardinal: PluralRules::cardinal(locale),
+            ordinal: PluralRules::ordinal(locale),
+            tags,
+        }
+    }
+
+    pub fn render(
+        &self,
+        node: &AstNode,
+        arguments: &IndexMap<String, Value>,
+    ) -> Result<String, RenderError> {
+        let mut writer = Writer {
+            out: String::new(),
+            renderer: self,
+            arguments,
+        };
+        writer.node(node, None)?;
+        Ok(writer.out)
+    }
+}
+
+struct Writer<'r> {
+    out: String,
+    renderer: &'r Renderer,
+    arguments: &'r IndexMap<String, Value>,
+}
+
+impl Writer<'_> {
+    fn nodes(&mut self, nodes: &[AstNode], number: Option<f64>) -> Result<(), RenderError> {
+        for node in nodes {
+            self.node(node, number)?;
+        }
+        Ok(())
+    }
+
+    /// Writes `node`; `number` is what `#` stands for in the enclosing
+    /// plural option.
+    fn node(&mut self, node: &AstNode, number: Option<f64>) -> Result<(), RenderError> {
+        match node {
+            AstNode::Root(children, _) => self.nodes(children, number)?,
+            AstNode::Text(text, _) => self.out.push_str(text),
+            AstNode::Variable(variable, span) => {
+                let value = self.argument(variable, *span)?;
+                self.out.push_str(&value.to_string());
+            }
+            AstNode::Pound(_) => match number {
+                Some(number) => self.out.push_str(&format_number(number, None)),
+                None => self.out.push('#'),
+            },
+            AstNode::Plural {
+                variable,
+                offset,
+                options,
+                span,
+            } => {
+                let value = self.number(variable, *span)?;
+                let adjusted = value - f64::from(*offset);
+                let category = self.renderer.cardinal.category(adjusted);
+                if let Some(body) = choose(options, value, category) {
+                    self.nodes(body, Some(adjusted))?;
+                }
+            }
+            AstNode::SelectOrdinal {
+                variable,
+                options,
+                span,
+            } => {
+                let value = self.number(variable, *span)?;
+                let category = self.renderer.ordinal.category(value);
+                if let Some(body) = choose(options, value, category) {
+                    self.nodes(body, Some(value))?;
+                }
+            }
+            AstNode::Select {
+                variable,
+                options,
+                span,
+            } => {
+                let value = self.argument(variable, *span)?.to_string();
+                if let Some(body) = options.get(&value).or_else(|| options.get("other")) {
+                    self.nodes(body, number)?;
+                }
+            }
+            AstNode::Number {
+                variable,
+                style,
+                span,
+            } => {
+                let value = self.number(variable, *span)?;
+                self.out.push_str(&format_number(value, style.as_deref()));
+            }
+            AstNode::Date { variable, span, .. } => {
+                return Err(unsupported(variable, "date", *span));
+            }
+            AstNode::Time { variable, span, .. } => {
+                return Err(unsupported(variable, "time", *span));
+            }
+            AstNode::HtmlTag {
+                name,
+                attributes,
+                self_closing,
+                children,
+                ..
+            } => match self.renderer.tags {
+                Tags::Keep => self.tag(name, attributes, *self_closing, children, number)?,
+                Tags::Strip => self.nodes(children, number)?,
+            },
+            AstNode::Interpolation {
+                variable,
+                formatters,
+                escaped,
+                span,
+            } => {
+                let number = formatters.iter().any(|formatter| formatter.is_number());
+                if !number && formatters.iter().any(|formatter| formatter.is_date()) {
+                    return Err(unsupported(variable, "datetime", *span));
+                }
+                let text = if number {
+                    format_number(self.number(variable, *span)?, None)
+                } else {
+                    self.argument(variable, *span)?.to_string()
+                };
+                if *escaped {
+                    escape_html(&text, &mut self.out);
+                } else {
+                    self.out.push_str(&text);
+                }
+            }
+            AstNode::Error { recovered, .. } => {
+                if let Some(node) = recovered {
+                    self.node(node, number)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn tag(
+        &mut self,
+        name: &str,
+        attributes: &[Attribute],
+        self_closing: bool,
+        children: &[AstNode],
+        number: Option<f64>,
+    ) -> Result<(), RenderError> {
+        self.out.push('<');
+        self.out.push_str(name);
+        for attribute in attributes {
+            self.out.push(' ');
+            self.out.push_str(&attribute.name);
+            if let Some(value) = &attribute.value {
+                let quote = if value.contains('"') { '\'' } else { '"' };
+                self.out.push('=');
+                self.out.push(quote);
+                self.out.push_str(value);
+                self.out.push(quote);
+            }
+        }
+        if self_closing && children.is_empty() {
+            self.out.push_str("/>");
+            return Ok(());
+        }
+        self.out.push('>');
+        self.nodes(children, number)?;
+        self.out.push_str("</");
+        self.out.push_str(name);
+        self.out.push('>');
+        Ok(())
+    }
+
+    /// The value of `name`, which may be a dotted path into an argument.
+    fn argument(&self, name: &str, span: Span) -> Result<&'_ Value, RenderError> {
+        let value = match name.split_once('.') {
+            Some((root, path)) => self.arguments.get(root).and_then(|value| value.field(path)),
+            None => self.arguments.get(name),
+        };
+        value.ok_or_else(|| {
+            RenderError::new(
+                RenderErrorKind::MissingArgument {
+                    name: name.to_string(),
+                },
+                span,
+            )
+        })
+    }
+
+    fn number(&self, name: &str, span: Span) -> Result<f64, RenderError> {
+        let value = self.argument(name, span)?;
+        value.as_number().ok_or_else(|| {
+            RenderError::new(
+                RenderErrorKind::NotANumber {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                },
+                span,
+            )
+        })
+    }
+}
+
+fn unsupported(name: &str, kind: &'static str, span: Span) -> RenderError {
+    RenderError::new(
+        RenderErrorKind::Unsupported {
+            name: name.to_string(),
+            kind,
+        },
+        span,
+    )
+}
+
+/// Picks the option for `value`: an exact `=value` match, then `category`,
+/// then `other`.
+fn choose(options: &Options, value: f64, category: PluralCategory) -> Option<&Vec<AstNode>> {
+    options
+        .iter()
+        .find(|(key, _)| {
+            key.strip_prefix('=')
+                .and_then(|exact| exact.parse::<f64>().ok())
+                .is_some_and(|exact| exact == value)
+        })
+        .map(|(_, body)| body)
+        .or_else(|| options.get(category.as_str()))
+        .or_else(|| options.get("other"))
+}
+
+/// Formats a number for `{n, number, style}`. `integer` rounds, `percent`
+/// multiplies by 100; anything else keeps up to three fraction digits.
+fn format_number(number: f64, style: Option<&str>) -> String {
+    match style {
+        Some("integer") => format!("{}", number.round()),
+        Some("percent") => format!("{}%", (number * 100.0).round()),
+        _ => format!("{}", (number * 1000.0).round() / 1000.0),
+    }
+}
+
+/// Escapes an i18next value the way i18next does by default.
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            '/' => out.push_str("&#x2F;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::parse::Parser;
+
+    fn render(
+        message: &str,
+        dialect: Dialect,
+        arguments: &[(&str, Value)],
+    ) -> Result<String, RenderError> {
+        let mut values = IndexMap::new();
+        for (path, value) in arguments {
+            insert_argument(&mut values, path, value.clone());
+        }
+        let node = Parser::with_dialect(message, dialect).parse().unwrap();
+        Renderer::new("en").render(&node, &values)
+    }
+
+    #[test]
+    fn reads_dotted_arguments_from_their_root() {
+        let arguments = [("user.name", "Ann".into()), ("user.age", 3.into())];
+        assert_eq!(
+            render(
+                "{{user.name}} is {{user.age}}",
+                Dialect::I18next,
+                &arguments
+            )
+            .unwrap(),
+            "Ann is 3"
+        );
+        let error = render("{{user.email}}", Dialect::I18next, &arguments).unwrap_err();
+        assert_eq!(
+            error.kind,
+            RenderErrorKind::MissingArgument {
+                name: "user.email".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn refuses_dates_and_times() {
+        for (message, dialect, kind) in [
+            ("{d, date, short}", Dialect::Icu, "date"),
+            ("{t, time}", Dialect::Icu, "time"),
+            ("{{d, datetime}}", Dialect::I18next, "datetime"),
+        ] {
+            let error = render(message, dialect, &[("d", 0.into()), ("t", 0.into())]).unwrap_err();
+            assert!(
+                matches!(error.kind, RenderErrorKind::Unsupported { kind: found, .. } if found == kind),
+                "{:?}: {:?}",
+                message,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn writes_text_arguments_as_they_are() {
+        for value in ["01234", "Infinity", "nan", "1e3"] {
+            assert_eq!(
+                render("Zip {zip}", Dialect::Icu, &[("zip", value.into())]).unwrap(),
+                format!("Zip {}", value)
+            );
+        }
+    }
+
+    #[test]
+    fn picks_plural_options() {
+        let message = "{n, plural, =0 {none} one {# item} other {# items}}";
+        for (n, expected) in [("0", "none"), ("1", "1 item"), ("2.5", "2.5 items")] {
+            assert_eq!(
+                render(message, Dialect::Icu, &[("n", n.into())]).unwrap(),
+                expected
+            );
+        }
+        for n in ["Infinity", "nan", "many"] {
+            let error = render(message, Dialect::Icu, &[("n", n.into())]).unwrap_err();
+            assert!(
+                matches!(error.kind, RenderErrorKind::NotANumber { .. }),
+                "{}: {:?}",
+                n,
+                error
+            );
+        }
+        let error = render(message, Dialect::Icu, &[("n", f64::INFINITY.into())]).unwrap_err();
+        assert!(matches!(error.kind, RenderErrorKind::NotANumber { .. }));
+    }
+
+    #[test]
+    fn subtracts_the_offset_for_pound() {
+        let message =
+            "{n, plural, offset:1 =0 {nobody} =1 {{host}} one {{host} and # other} other {{host} and # others}}";
+        let host = ("host", Value::from("Ann"));
+        for (n, expected) in [
+            (0, "nobody"),
+            (1, "Ann"),
+            (2, "Ann and 1 other"),
+            (4, "Ann and 3 others"),
+        ] {
+            assert_eq!(
+                render(message, Dialect::Icu, &[("n", n.into()), host.clone()]).unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            render("# {n, number}", Dialect::Icu, &[("n", "7".into())]).unwrap(),
+            "# 7"
+        );
+    }
+
+    #[test]
+    fn picks_selectordinal_options() {
+        let message = "{n, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}";
+        for (n, expected) in [
+            (1, "1st"),
+            (2, "2nd"),
+            (3, "3rd"),
+            (4, "4th"),
+            (11, "11th"),
+            (22, "22nd"),
+        ] {
+            assert_eq!(
+                render(message, Dialect::Icu, &[("n", n.into())]).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn picks_select_options() {
+        let message = "{role, select, admin {Admin} 1 {One} other {User {role}}}";
+        for (role, expected) in [
+            (Value::from("admin"), "Admin"),
+            (Value::from(1), "One"),
+            (Value::from("guest"), "User guest"),
+        ] {
+            assert_eq!(
+                render(message, Dialect::Icu, &[("role", role)]).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_or_strips_tags() {
+        let node = Parser::new("Read <a href=\"/terms\">the <b>terms</b></a><br/>")
+            .parse()
+            .unwrap();
+        let arguments = IndexMap::new();
+        assert_eq!(
+            Renderer::with_tags("en", Tags::Keep)
+                .render(&node, &arguments)
+                .unwrap(),
+            "Read <a href=\"/terms\">the <b>terms</b></a><br/>"
+        );
+        assert_eq!(
+            Renderer::with_tags("en", Tags::Strip)
+                .render(&node, &arguments)
+                .unwrap(),
+            "Read the terms"
+        );
+    }
+}
@@ -0,0 +1,749 @@
+//! The JSON form of a parsed message.
+//!
+//! A message is a document `{"version": 1, "ast": node}` and a catalog is
+//! `{"version": 1, "messages": {"key": node, ...}}` with keys in file order.
+//! The version changes whenever a document written by one version could be
+//! read differently by another.
+//!
+//! Every node is an object with a `type` and a `span`, `{"start": 0, "end": 5}`,
+//! in bytes from the start of the message:
+//!
+//! - `root`: `children`, an array of nodes.
+//! - `text`: `value`, the text with quotes already resolved.
+//! - `variable`: `name`.
+//! - `plural`: `variable`, `offset` and `options`, an object mapping each key
+//!   such as `one` or `=0` to an array of nodes, in source order.
+//! - `selectordinal` and `select`: `variable` and `options`.
+//! - `number`, `date` and `time`: `variable` and `style`, a string or `null`.
+//! - `html_tag`: `name`, `attributes` (objects with `name` and a `value` that
+//!   may be `null`), `numbered`, `self_closing` and `children`.
+//! - `interpolation`: `variable`, `formatters` (objects with `name` and
+//!   `options`, a string or `null`) and `escaped`.
+//! - `pound`: nothing else.
+//! - `error`: `error` and `recovered`, a node or `null`. The error has the
+//!   diagnostic `code`, e.g. `typed_key::missing_other`, a `message` for
+//!   people, the fields of that error such as `variable` and `kind`, and its
+//!   own `span`.
+
+use indexmap::IndexMap;
+use serde_json::{json, Map, Value as JsonValue};
+
+use crate::dialect::Formatter;
+use crate::error::{ParseError, ParseErrorKind, SchemaError, SchemaErrorKind};
+use crate::parse::{AstNode, Attribute, Options};
+use crate::span::Span;
+
+type Object = Map<String, JsonValue>;
+
+/// The schema version this build writes and reads.
+pub const VERSION: u64 = 1;
+
+impl AstNode {
+    /// The message as a versioned JSON document.
+    pub fn to_json(&self) -> JsonValue {
+        json!({
+            "version": VERSION,
+            "ast": node_to_json(self),
+        })
+    }
+
+    /// Reads a document written by `to_json`.
+    pub fn from_json(document: &JsonValue) -> Result<AstNode, SchemaError> {
+        let document = object(document, "")?;
+        version(document)?;
+        node_from_json(field(document, "ast", "")?, "/ast")
+    }
+}
+
+/// A catalog of parsed messages as a versioned JSON document.
+pub fn catalog_to_json<'a>(
+    messages: impl IntoIterator<Item = (&'a String, &'a AstNode)>,
+) -> JsonValue {
+    let messages: Object = messages
+        .into_iter()
+        .map(|(key, node)| (key.clone(), node_to_json(node)))
+        .collect();
+    json!({
+        "version": VERSION,
+        "messages": messages,
+    })
+}
+
+/// Reads a document written by `catalog_to_json`.
+pub fn catalog_from_json(document: &JsonValue) -> Result<IndexMap<String, AstNode>, SchemaError> {
+    let document = object(document, "")?;
+    version(document)?;
+    let messages = object(field(document, "messages", "")?, "/messages")?;
+    messages
+        .iter()
+        .map(|(key, node)| {
+            let path = child("/messages", key);
+            Ok((key.clone(), node_from_json(node, &path)?))
+        })
+        .collect()
+}
+
+fn node_to_json(node: &AstNode) -> JsonValue {
+    match node {
+        AstNode::Root(children, span) => json!({
+            "type": "root",
+            "children": nodes_to_json(children),
+            "span": span,
+        }),
+        AstNode::Text(text, span) => json!({
+            "type": "text",
+            "value": text,
+            "span": span,
+        }),
+        AstNode::Variable(name, span) => json!({
+            "type": "variable",
+            "name": name,
+            "span": span,
+        }),
+        AstNode::Plural {
+            variable,
+            offset,
+            options,
+            span,
+        } => json!({
+            "type": "plural",
+            "variable": variable,
+            "offset": offset,
+            "options": options_to_json(options),
+            "span": span,
+        }),
+        AstNode::SelectOrdinal {
+            variable,
+            options,
+            span,
+        } => json!({
+            "type": "selectordinal",
+            "variable": variable,
+            "options": options_to_json(options),
+            "span": span,
+        }),
+        AstNode::Select {
+            variable,
+            options,
+            span,
+        } => json!({
+            "type": "select",
+            "variable": variable,
+            "options": options_to_json(options),
+            "span": span,
+        }),
+        AstNode::Number {
+            variable,
+            style,
+            span,
+        } => json!({
+            "type": "number",
+            "variable": variable,
+            "style": style,
+            "span": span,
+        }),
+        AstNode::Date {
+            variable,
+            style,
+            span,
+        } => json!({
+            "type": "date",
+            "variable": variable,
+            "style": style,
+            "span": span,
+        }),
+        AstNode::Time {
+            variable,
+            style,
+            span,
+        } => json!({
+            "type": "time",
+            "variable": variable,
+            "style": style,
+            "span": span,
+        }),
+        AstNode::HtmlTag {
+            name,
+            attributes,
+            numbered,
+            self_closing,
+            children,
+            span,
+        } => json!({
+            "type": "html_tag",
+            "name": name,
+            "attributes": attributes
+                .iter()
+                .map(|attribute| json!({
+                    "name": attribute.name,
+                    "value": attribute.value,
+                }))
+                .collect::<Vec<_>>(),
+            "numbered": numbered,
+            "self_closing": self_closing,
+            "children": nodes_to_json(children),
+            "span": span,
+        }),
+        AstNode::Interpolation {
+            variable,
+            formatters,
+            escaped,
+            span,
+        } => json!({
+            "type": "interpolation",
+            "variable": variable,
+            "formatters": formatters
+                .iter()
+                .map(|formatter| json!({
+                    "name": formatter.name,
+                    "options": formatter.options,
+                }))
+                .collect::<Vec<_>>(),
+            "escaped": escaped,
+            "span": span,
+        }),
+        AstNode::Pound(span) => json!({
+            "type": "pound",
+            "span": span,
+        }),
+        AstNode::Error {
+            error,
+            recovered,
+            span,
+        } => json!({
+            "type": "error",
+            "error": error_to_json(error),
+            "recovered": recovered.as_deref().map(node_to_json),
+            "span": span,
+        }),
+    }
+}
+
+fn nodes_to_json(nodes: &[AstNode]) -> JsonValue {
+    nodes.iter().map(node_to_json).collect()
+}
+
+fn options_to_json(options: &Options) -> JsonValue {
+    options
+        .iter()
+        .map(|(key, nodes)| (key.clone(), nodes_to_json(nodes)))
+        .collect::<Map<_, _>>()
+        .into()
+}
+
+fn error_to_json(error: &ParseError) -> JsonValue {
+    let mut object = Map::new();
+    object.insert("code".into(), error.code().into());
+    object.insert("message".into(), error.kind.to_string().into());
+    let fields: &[(&str, &str)] = match &error.kind {
+        ParseErrorKind::UnclosedArgument
+        | ParseErrorKind::EmptyArgument
+        | ParseErrorKind::InvalidEntry
+        | ParseErrorKind::InvalidExpression
+        | ParseErrorKind::UnclosedString => &[],
+        ParseErrorKind::MissingOptions { variable, kind }
+        | ParseErrorKind::MissingOther { variable, kind } => {
+            &[("variable", variable), ("kind", kind)]
+        }
+        ParseErrorKind::UnclosedOption { key } | ParseErrorKind::MissingOptionBody { key } => {
+            &[("key", key)]
+        }
+        ParseErrorKind::InvalidCategory { category, kind } => {
+            &[("category", category), ("kind", kind)]
+        }
+        ParseErrorKind::InvalidOffset { offset } => &[("offset", offset)],
+        ParseErrorKind::MissingCategories {
+            variable,
+            kind,
+            locale,
+            categories,
+        } => &[
+            ("variable", variable),
+            ("kind", kind),
+            ("locale", locale),
+            ("categories", categories),
+        ],
+        ParseErrorKind::BracedArgumentName { name }
+        | ParseErrorKind::UnclosedTag { name }
+        | ParseErrorKind::UnexpectedClosingTag { name } => &[("name", name)],
+        ParseErrorKind::MismatchedTag { expected, found } => {
+            &[("expected", expected), ("found", found)]
+        }
+        ParseErrorKind::MissingDefaultVariant { variable }
+        | ParseErrorKind::RepeatedDefaultVariant { variable } => &[("variable", variable)],
+    };
+    for (name, value) in fields {
+        object.insert(name.to_string(), (*value).into());
+    }
+    object.insert("span".into(), json!(error.span));
+    object.into()
+}
+
+fn node_from_json(value: &JsonValue, path: &str) -> Result<AstNode, SchemaError> {
+    let node = object(value, path)?;
+    let node_type = string(node, "type", path)?;
+    let span = span(node, path)?;
+    Ok(match node_type.as_str() {
+        "root" => AstNode::Root(nodes(node, "children", path)?, span),
+        "text" => AstNode::Text(string(node, "value", path)?, span),
+        "variable" => AstNode::Variable(string(node, "name", path)?, span),
+        "plural" => AstNode::Plural {
+            variable: string(node, "variable", path)?,
+            offset: offset(node, path)?,
+            options: options(node, path)?,
+            span,
+        },
+        "selectordinal" => AstNode::SelectOrdinal {
+            variable: string(node, "variable", path)?,
+            options: options(node, path)?,
+            span,
+        },
+        "select" => AstNode::Select {
+            variable: string(node, "variable", path)?,
+            options: options(node, path)?,
+            span,
+        },
+        "number" => AstNode::Number {
+            variable: string(node, "variable", path)?,
+            style: optional_string(node, "style", path)?,
+            span,
+        },
+        "date" => AstNode::Date {
+            variable: string(node, "variable", path)?,
+            style: optional_string(node, "style", path)?,
+            span,
+        },
+        "time" => AstNode::Time {
+            variable: string(node, "variable", path)?,
+            style: optional_string(node, "style", path)?,
+            span,
+        },
+        "html_tag" => AstNode::HtmlTag {
+            name: string(node, "name", path)?,
+            attributes: objects(node, "attributes", path)?
+                .into_iter()
+                .map(|(attribute, path)| {
+                    Ok(Attribute {
+                        name: string(attribute, "name", &path)?,
+                        value: optional_string(attribute, "value", &path)?,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            numbered: boolean(node, "numbered", path)?,
+            self_closing: boolean(node, "self_closing", path)?,
+            children: nodes(node, "children", path)?,
+            span,
+        },
+        "interpolation" => AstNode::Interpolation {
+            variable: string(node, "variable", path)?,
+            formatters: objects(node, "formatters", path)?
+                .into_iter()
+                .map(|(formatter, path)| {
+                    Ok(Formatter {
+                        name: string(formatter, "name", &path)?,
+                        options: optional_string(formatter, "options", &path)?,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            escaped: boolean(node, "escaped", path)?,
+            span,
+        },
+        "pound" => AstNode::Pound(span),
+        "error" => AstNode::Error {
+            error: error_from_json(field(node, "error", path)?, &child(path, "error"))?,
+            recovered: match node.get("recovered") {
+                None | Some(JsonValue::Null) => None,
+                Some(recovered) => Some(Box::new(node_from_json(
+                    recovered,
+                    &child(path, "recovered"),
+                )?)),
+            },
+            span,
+        },
+        _ => {
+            return Err(SchemaError::new(
+                SchemaErrorKind::UnknownNodeType { name: node_type },
+                child(path, "type"),
+            ))
+        }
+    })
+}
+
+fn error_from_json(value: &JsonValue, path: &str) -> Result<ParseError, SchemaError> {
+    let error = object(value, path)?;
+    let text = |name| string(error, name, path);
+    let code = text("code")?;
+    let kind = match code.as_str() {
+        "typed_key::unclosed_argument" => ParseErrorKind::UnclosedArgument,
+        "typed_key::empty_argument" => ParseErrorKind::EmptyArgument,
+        "typed_key::braced_argument_name" => ParseErrorKind::BracedArgumentName {
+            name: text("name")?,
+        },
+        "typed_key::missing_options" => ParseErrorKind::MissingOptions {
+            variable: text("variable")?,
+            kind: argument_kind(error, path)?,
+        },
+        "typed_key::unclosed_option" => ParseErrorKind::UnclosedOption { key: text("key")? },
+        "typed_key::missing_option_body" => ParseErrorKind::MissingOptionBody { key: text("key")? },
+        "typed_key::invalid_category" => ParseErrorKind::InvalidCategory {
+            category: text("category")?,
+            kind: argument_kind(error, path)?,
+        },
+        "typed_key::invalid_offset" => ParseErrorKind::InvalidOffset {
+            offset: text("offset")?,
+        },
+        "typed_key::missing_other" => ParseErrorKind::MissingOther {
+            variable: text("variable")?,
+            kind: argument_kind(error, path)?,
+        },
+        "typed_key::missing_categories" => ParseErrorKind::MissingCategories {
+            variable: text("variable")?,
+            kind: argument_kind(error, path)?,
+            locale: text("locale")?,
+            categories: text("categories")?,
+        },
+        "typed_key::unclosed_tag" => ParseErrorKind::UnclosedTag {
+            name: text("name")?,
+        },
+        "typed_key::mismatched_tag" => ParseErrorKind::MismatchedTag {
+            expected: text("expected")?,
+            found: text("found")?,
+        },
+        "typed_key::unexpected_closing_tag" => ParseErrorKind::UnexpectedClosingTag {
+            name: text("name")?,
+        },
+        "typed_key::invalid_entry" => ParseErrorKind::InvalidEntry,
+        "typed_key::invalid_expression" => ParseErrorKind::InvalidExpression,
+        "typed_key::unclosed_string" => ParseErrorKind::UnclosedString,
+        "typed_key::missing_default_variant" => ParseErrorKind::MissingDefaultVariant {
+            variable: text("variable")?,
+        },
+        "typed_key::repeated_default_variant" => ParseErrorKind::RepeatedDefaultVariant {
+            variable: text("variable")?,
+        },
+        _ => {
+            return Err(SchemaError::new(
+                SchemaErrorKind::UnknownErrorCode { code },
+                child(path, "code"),
+            ))
+        }
+    };
+    Ok(ParseError::new(kind, span(error, path)?))
+}
+
+fn version(document: &Object) -> Result<(), SchemaError> {
+    let version = field(document, "version", "")?;
+    match version.as_u64() {
+        Some(VERSION) => Ok(()),
+        Some(_) => Err(SchemaError::new(
+            SchemaErrorKind::UnsupportedVersion {
+                version: version.to_string(),
+            },
+            "/version".to_string(),
+        )),
+        None => Err(invalid("a whole number", "/version")),
+    }
+}
+
+fn field<'v>(object: &'v Object, name: &str, path: &str) -> Result<&'v JsonValue, SchemaError> {
+    object.get(name).ok_or_else(|| {
+        let path = if path.is_empty() { "/" } else { path };
+        SchemaError::new(
+            SchemaErrorKind::MissingField {
+                field: name.to_string(),
+            },
+            path.to_string(),
+        )
+    })
+}
+
+fn object<'v>(value: &'v JsonValue, path: &str) -> Result<&'v Object, SchemaError> {
+    value.as_object().ok_or_else(|| invalid("an object", path))
+}
+
+fn string(object: &Object, name: &str, path: &str) -> Result<String, SchemaError> {
+    field(object, name, path)?
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| invalid("a string", &child(path, name)))
+}
+
+/// A string that may be `null` or left out.
+fn optional_string(object: &Object, name: &str, path: &str) -> Result<Option<String>, SchemaError> {
+    match object.get(name) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(JsonValue::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(invalid("a string or null", &child(path, name))),
+    }
+}
+
+fn boolean(object: &Object, name: &str, path: &str) -> Result<bool, SchemaError> {
+    field(object, name, path)?
+        .as_bool()
+        .ok_or_else(|| invalid("true or false", &child(path, name)))
+}
+
+fn offset(object: &Object, path: &str) -> Result<u32, SchemaError> {
+    field(object, "offset", path)?
+        .as_u64()
+        .and_then(|offset| u32::try_from(offset).ok())
+        .ok_or_else(|| invalid("a whole number", &child(path, "offset")))
+}
+
+fn span(object: &Object, path: &str) -> Result<Span, SchemaError> {
+    let span = field(object, "span", path)?;
+    let bound = |name| span.get(name).and_then(JsonValue::as_u64);
+    match (bound("start"), bound("end")) {
+        (Some(start), Some(end)) if start <= end => Ok(Span::new(start as usize, end as usize)),
+        _ => Err(invalid("`start` and `end` offsets", &child(path, "span"))),
+    }
+}
+
+/// The `kind` of an error: the argument type, or `ordinal` for the
+/// categories of a selectordinal.
+fn argument_kind(object: &Object, path: &str) -> Result<&'static str, SchemaError> {
+    match string(object, "kind", path)?.as_str() {
+        "plural" => Ok("plural"),
+        "selectordinal" => Ok("selectordinal"),
+        "select" => Ok("select"),
+        "ordinal" => Ok("ordinal"),
+        _ => Err(invalid(
+            "`plural`, `selectordinal`, `select` or `ordinal`",
+            &child(path, "kind"),
+        )),
+    }
+}
+
+/// The items of an array field, each with its path.
+fn items<'v>(
+    object: &'v Object,
+    name: &str,
+    path: &str,
+) -> Result<impl Iterator<Item = (&'v JsonValue, String)>, SchemaError> {
+    let array = field(object, name, path)?
+        .as_array()
+        .ok_or_else(|| invalid("an array", &child(path, name)))?;
+    let path = child(path, name);
+    Ok(array
+        .iter()
+        .enumerate()
+        .map(move |(index, item)| (item, format!("{}/{}", path, index))))
+}
+
+/// The objects of an array field, each with its path.
+fn objects<'v>(
+    object: &'v Object,
+    name: &str,
+    path: &str,
+) -> Result<Vec<(&'v Object, String)>, SchemaError> {
+    items(object, name, path)?
+        .map(|(item, path)| Ok((self::object(item, &path)?, path)))
+        .collect()
+}
+
+fn nodes(object: &Object, name: &str, path: &str) -> Result<Vec<AstNode>, SchemaError> {
+    items(object, name, path)?
+        .map(|(node, path)| node_from_json(node, &path))
+        .collect()
+}
+
+fn options(object: &Object, path: &str) -> Result<Options, SchemaError> {
+    let options = self::object(field(object, "options", path)?, &child(path, "options"))?;
+    let path = child(path, "options");
+    options
+        .keys()
+        .map(|key| Ok((key.clone(), nodes(options, key, &path)?)))
+        .collect()
+}
+
+fn invalid(expected: &'static str, path: &str) -> SchemaError {
+    SchemaError::new(SchemaErrorKind::InvalidValue { expected }, path.to_string())
+}
+
+/// The JSON pointer of `name` in the object at `path`. In keys, `~` is
+/// written `~0` and `/` is written `~1`.
+fn child(path: &str, name: &str) -> String {
+    format!("{}/{}", path, name.replace('~', "~0").replace('/', "~1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+    use crate::parse::Parser;
+
+    const MESSAGES: [&str; 5] = [
+        "Hi {name}, it''s '{'{count, plural, offset:1 =0 {none} one {# left} other {# and {rest}}}",
+        "{place, selectordinal, one {#st} other {#th}} {role, select, admin {Admin} other {User}}",
+        "{n, number, percent} {d, date, short} {t, time} {n, number}",
+        "Read <a href=\"/terms\" download>the <b>terms</b></a><br/> <0>zero</0><1/>",
+        "{n, plural, one {x} few {y}} <b>open {x",
+    ];
+
+    fn parse(message: &str, dialect: Dialect) -> AstNode {
+        Parser::with_dialect(message, dialect).parse_recovering().0
+    }
+
+    #[test]
+    fn round_trips_every_node() {
+        let mut nodes: Vec<_> = MESSAGES
+            .iter()
+            .map(|message| parse(message, Dialect::Icu))
+            .collect();
+        nodes.push(parse(
+            "{{user.name}} {{- html}} {{d, datetime(format: short); uppercase}}",
+            Dialect::I18next,
+        ));
+        assert!(nodes[4]
+            .to_json()
+            .to_string()
+            .contains("typed_key::missing_other"));
+        for node in nodes {
+            let document = node.to_json();
+            assert_eq!(document["version"], VERSION);
+            assert_eq!(AstNode::from_json(&document).unwrap(), node);
+        }
+    }
+
+    #[test]
+    fn writes_the_documented_shape() {
+        assert_eq!(
+            parse("Hi {name}", Dialect::Icu).to_json(),
+            json!({
+                "version": 1,
+                "ast": {
+                    "type": "root",
+                    "children": [
+                        {"type": "text", "value": "Hi ", "span": {"start": 0, "end": 3}},
+                        {"type": "variable", "name": "name", "span": {"start": 3, "end": 9}},
+                    ],
+                    "span": {"start": 0, "end": 9},
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn round_trips_catalogs_in_key_order() {
+        let messages: IndexMap<String, AstNode> = ["zebra", "apple", "mango"]
+            .into_iter()
+            .zip(MESSAGES)
+            .map(|(key, message)| (key.to_string(), parse(message, Dialect::Icu)))
+            .collect();
+        let read = catalog_from_json(&catalog_to_json(&messages)).unwrap();
+        assert_eq!(read, messages);
+        assert_eq!(read.keys().collect::<Vec<_>>(), ["zebra", "apple", "mango"]);
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut document = parse("Hi", Dialect::Icu).to_json();
+        document["version"] = json!(2);
+        let error = AstNode::from_json(&document).unwrap_err();
+        assert_eq!(
+            error,
+            SchemaError::new(
+                SchemaErrorKind::UnsupportedVersion {
+                    version: "2".to_string()
+                },
+                "/version".to_string()
+            )
+        );
+
+        document["version"] = json!("1");
+        let error = AstNode::from_json(&document).unwrap_err();
+        assert_eq!(
+            error.kind,
+            SchemaErrorKind::InvalidValue {
+                expected: "a whole number"
+            }
+        );
+
+        let error = catalog_from_json(&json!({"messages": {}})).unwrap_err();
+        assert_eq!(
+            error,
+            SchemaError::new(
+                SchemaErrorKind::MissingField {
+                    field: "version".to_string()
+                },
+                "/".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_bad_node_shapes() {
+        let span = json!({"start": 0, "end": 1});
+        let cases = [
+            (
+                json!({"span": span}),
+                SchemaErrorKind::MissingField {
+                    field: "type".to_string(),
+                },
+                "/ast",
+            ),
+            (
+                json!({"type": "paragraph", "span": span}),
+                SchemaErrorKind::UnknownNodeType {
+                    name: "paragraph".to_string(),
+                },
+                "/ast/type",
+            ),
+            (
+                json!({"type": "text", "value": 1, "span": span}),
+                SchemaErrorKind::InvalidValue {
+                    expected: "a string",
+                },
+                "/ast/value",
+            ),
+            (
+                json!({"type": "root", "children": {}, "span": span}),
+                SchemaErrorKind::InvalidValue {
+                    expected: "an array",
+                },
+                "/ast/children",
+            ),
+            (
+                json!({"type": "select", "variable": "x", "options": [], "span": span}),
+                SchemaErrorKind::InvalidValue {
+                    expected: "an object",
+                },
+                "/ast/options",
+            ),
+            (
+                json!({
+                    "type": "select",
+                    "variable": "x",
+                    "options": {"a/b": [{"type": "pound", "span": {"start": 0}}]},
+                    "span": span,
+                }),
+                SchemaErrorKind::InvalidValue {
+                    expected: "`start` and `end` offsets",
+                },
+                "/ast/options/a~1b/0/span",
+            ),
+            (
+                json!({
+                    "type": "error",
+                    "error": {"code": "typed_key::nope", "span": span},
+                    "span": span,
+                }),
+                SchemaErrorKind::UnknownErrorCode {
+                    code: "typed_key::nope".to_string(),
+                },
+                "/ast/error/code",
+            ),
+            (
+                json!([]),
+                SchemaErrorKind::InvalidValue {
+                    expected: "an object",
+                },
+                "/ast",
+            ),
+        ];
+        for (ast, kind, path) in cases {
+            let error = AstNode::from_json(&json!({"version": 1, "ast": ast})).unwrap_err();
+            assert_eq!(error, SchemaError::new(kind, path.to_string()), "{}", ast);
+        }
+    }
+}
@@ -0,0 +1,522 @@
+use crate::dialect::Formatter;
+use crate::error::ParseError;
+use crate::parse::{AstNode, Attribute, Options};
+use crate::span::Span;
+
+/// Walks a message without changing it.
+///
+/// There is one method per kind of node. The defaults visit the node's
+/// children, so an implementation only overrides the nodes it cares about
+/// and calls the matching `walk_*` function if it still wants to descend.
+pub trait Visit {
+    fn visit_node(&mut self, node: &AstNode) {
+        walk_node(self, node);
+    }
+
+    fn visit_root(&mut self, children: &[AstNode], _span: Span) {
+        walk_nodes(self, children);
+    }
+
+    fn visit_text(&mut self, _text: &str, _span: Span) {}
+
+    fn visit_variable(&mut self, _name: &str, _span: Span) {}
+
+    fn visit_pound(&mut self, _span: Span) {}
+
+    fn visit_plural(&mut self, _variable: &str, _offset: u32, options: &Options, _span: Span) {
+        walk_options(self, options);
+    }
+
+    fn visit_select_ordinal(&mut self, _variable: &str, options: &Options, _span: Span) {
+        walk_options(self, options);
+    }
+
+    fn visit_select(&mut self, _variable: &str, options: &Options, _span: Span) {
+        walk_options(self, options);
+    }
+
+    /// One `key {message}` option of a plural, selectordinal or select.
+    fn visit_option(&mut self, _key: &str, body: &[AstNode]) {
+        walk_nodes(self, body);
+    }
+
+    fn visit_number(&mut self, _variable: &str, _style: Option<&str>, _span: Span) {}
+
+    fn visit_date(&mut self, _variable: &str, _style: Option<&str>, _span: Span) {}
+
+    fn visit_time(&mut self, _variable: &str, _style: Option<&str>, _span: Span) {}
+
+    fn visit_html_tag(
+        &mut self,
+        _name: &str,
+        _attributes: &[Attribute],
+        _numbered: bool,
+        _self_closing: bool,
+        children: &[AstNode],
+        _span: Span,
+    ) {
+        walk_nodes(self, children);
+    }
+
+    fn visit_interpolation(
+        &mut self,
+        _variable: &str,
+        _formatters: &[Formatter],
+        _escaped: bool,
+        _span: Span,
+    ) {
+    }
+
+    /// A part of the message that failed to parse. The default visits what the
+    /// parser recovered.
+    fn visit_error(&mut self, _error: &ParseError, recovered: Option<&AstNode>, _span: Span) {
+        if let Some(node) = recovered {
+            self.visit_node(node);
+        }
+    }
+}
+
+/// Calls the `Visit` method for the kind of `node`.
+pub fn walk_node<V: Visit + ?Sized>(visitor: &mut V, node: &AstNode) {
+    match node {
+        AstNode::Root(children, span) => visitor.visit_root(children, *span),
+        AstNode::Text(text, span) => visitor.visit_text(text, *span),
+        AstNode::Variable(name, span) => visitor.visit_variable(name, *span),
+        AstNode::Pound(span) => visitor.visit_pound(*span),
+        AstNode::Plural {
+            variable,
+            offset,
+            options,
+            span,
+        } => visitor.visit_plural(variable, *offset, options, *span),
+        AstNode::SelectOrdinal {
+            variable,
+            options,
+            span,
+        } => visitor.visit_select_ordinal(variable, options, *span),
+        AstNode::Select {
+            variable,
+            options,
+            span,
+        } => visitor.visit_select(variable, options, *span),
+        AstNode::Number {
+            variable,
+            style,
+            span,
+        } => visitor.visit_number(variable, style.as_deref(), *span),
+        AstNode::Date {
+            variable,
+            style,
+            span,
+        } => visitor.visit_date(variable, style.as_deref(), *span),
+        AstNode::Time {
+            variable,
+            style,
+            span,
+        } => visitor.visit_time(variable, style.as_deref(), *span),
+        AstNode::HtmlTag {
+            name,
+            attributes,
+            numbered,
+            self_closing,
+            children,
+            span,
+        } => visitor.visit_html_tag(name, attributes, *numbered, *self_closing, children, *span),
+        AstNode::Interpolation {
+            variable,
+            formatters,
+            escaped,
+            span,
+        } => visitor.visit_interpolation(variable, formatters, *escaped, *span),
+        AstNode::Error {
+            error,
+            recovered,
+            span,
+        } => visitor.visit_error(error, recovered.as_deref(), *span),
+    }
+}
+
+pub fn walk_nodes<V: Visit + ?Sized>(visitor: &mut V, nodes: &[AstNode]) {
+    for node in nodes {
+        visitor.visit_node(node);
+    }
+}
+
+pub fn walk_options<V: Visit + ?Sized>(visitor: &mut V, options: &Options) {
+    for (key, body) in options {
+        visitor.visit_option(key, body);
+    }
+}
+
+/// Rebuilds a message, e.g. to rename an argument or rewrite its text.
+///
+/// Each method takes one node apart and returns the node to put in its
+/// place. The defaults fold the children and rebuild the node unchanged;
+/// the `rebuild_*` functions do the same from an override.
+pub trait Fold {
+    fn fold_node(&mut self, node: AstNode) -> AstNode {
+        rebuild_node(self, node)
+    }
+
+    fn fold_root(&mut self, children: Vec<AstNode>, span: Span) -> AstNode {
+        AstNode::Root(rebuild_nodes(self, children), span)
+    }
+
+    fn fold_text(&mut self, text: String, span: Span) -> AstNode {
+        AstNode::Text(text, span)
+    }
+
+    fn fold_variable(&mut self, name: String, span: Span) -> AstNode {
+        AstNode::Variable(name, span)
+    }
+
+    fn fold_pound(&mut self, span: Span) -> AstNode {
+        AstNode::Pound(span)
+    }
+
+    fn fold_plural(
+        &mut self,
+        variable: String,
+        offset: u32,
+        options: Options,
+        span: Span,
+    ) -> AstNode {
+        AstNode::Plural {
+            variable,
+            offset,
+            options: rebuild_options(self, options),
+            span,
+        }
+    }
+
+    fn fold_select_ordinal(&mut self, variable: String, options: Options, span: Span) -> AstNode {
+        AstNode::SelectOrdinal {
+            variable,
+            options: rebuild_options(self, options),
+            span,
+        }
+    }
+
+    fn fold_select(&mut self, variable: String, options: Options, span: Span) -> AstNode {
+        AstNode::Select {
+            variable,
+            options: rebuild_options(self, options),
+            span,
+        }
+    }
+
+    /// One `key {message}` option of a plural, selectordinal or select.
+    fn fold_option(&mut self, key: String, body: Vec<AstNode>) -> (String, Vec<AstNode>) {
+        (key, rebuild_nodes(self, body))
+    }
+
+    fn fold_number(&mut self, variable: String, style: Option<String>, span: Span) -> AstNode {
+        AstNode::Number {
+            variable,
+            style,
+            span,
+        }
+    }
+
+    fn fold_date(&mut self, variable: String, style: Option<String>, span: Span) -> AstNode {
+        AstNode::Date {
+            variable,
+            style,
+            span,
+        }
+    }
+
+    fn fold_time(&mut self, variable: String, style: Option<String>, span: Span) -> AstNode {
+        AstNode::Time {
+            variable,
+            style,
+            span,
+        }
+    }
+
+    fn fold_html_tag(
+        &mut self,
+        name: String,
+        attributes: Vec<Attribute>,
+        numbered: bool,
+        self_closing: bool,
+        children: Vec<AstNode>,
+        span: Span,
+    ) -> AstNode {
+        AstNode::HtmlTag {
+            name,
+            attributes,
+            numbered,
+            self_closing,
+            children: rebuild_nodes(self, children),
+            span,
+        }
+    }
+
+    fn fold_interpolation(
+        &mut self,
+        variable: String,
+        formatters: Vec<Formatter>,
+        escaped: bool,
+        span: Span,
+    ) -> AstNode {
+        AstNode::Interpolation {
+            variable,
+            formatters,
+            escaped,
+            span,
+        }
+    }
+
+    fn fold_error(
+        &mut self,
+        error: ParseError,
+        recovered: Option<Box<AstNode>>,
+        span: Span,
+    ) -> AstNode {
+        AstNode::Error {
+            error,
+            recovered: recovered.map(|node| Box::new(self.fold_node(*node))),
+            span,
+        }
+    }
+}
+
+/// Calls the `Fold` method for the kind of `node`.
+pub fn rebuild_node<F: Fold + ?Sized>(folder: &mut F, node: AstNode) -> AstNode {
+    match node {
+        AstNode::Root(children, span) => folder.fold_root(children, span),
+        AstNode::Text(text, span) => folder.fold_text(text, span),
+        AstNode::Variable(name, span) => folder.fold_variable(name, span),
+        AstNode::Pound(span) => folder.fold_pound(span),
+        AstNode::Plural {
+            variable,
+            offset,
+            options,
+            span,
+        } => folder.fold_plural(variable, offset, options, span),
+        AstNode::SelectOrdinal {
+            variable,
+            options,
+            span,
+        } => folder.fold_select_ordinal(variable, options, span),
+        AstNode::Select {
+            variable,
+            options,
+            span,
+        } => folder.fold_select(variable, options, span),
+        AstNode::Number {
+            variable,
+            style,
+            span,
+        } => folder.fold_number(variable, style, span),
+        AstNode::Date {
+            variable,
+            style,
+            span,
+        } => folder.fold_date(variable, style, span),
+        AstNode::Time {
+            variable,
+            style,
+            span,
+        } => folder.fold_time(variable, style, span),
+        AstNode::HtmlTag {
+            name,
+            attributes,
+            numbered,
+            self_closing,
+            children,
+            span,
+        } => folder.fold_html_tag(name, attributes, numbered, self_closing, children, span),
+        AstNode::Interpolation {
+            variable,
+            formatters,
+            escaped,
+            span,
+        } => folder.fold_interpolation(variable, formatters, escaped, span),
+        AstNode::Error {
+            error,
+            recovered,
+            span,
+        } => folder.fold_error(error, recovered, span),
+    }
+}
+
+pub fn rebuild_nodes<F: Fold + ?Sized>(folder: &mut F, nodes: Vec<AstNode>) -> Vec<AstNode> {
+    nodes
+        .into_iter()
+        .map(|node| folder.fold_node(node))
+        .collect()
+}
+
+pub fn rebuild_options<F: Fold + ?Sized>(folder: &mut F, options: Options) -> Options {
+    options
+        .into_iter()
+        .map(|(key, body)| folder.fold_option(key, body))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::Parser;
+    use crate::print::Printer;
+
+    const NESTED: &str = "{gender, select, female {<b>{name}</b> has {count, plural, one {# item} other {# items}}} other {{name}}}";
+
+    /// Records what it visits, in order.
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl Visit for Trace {
+        fn visit_text(&mut self, text: &str, _span: Span) {
+            self.0.push(format!("text {:?}", text));
+        }
+
+        fn visit_variable(&mut self, name: &str, _span: Span) {
+            self.0.push(format!("variable {}", name));
+        }
+
+        fn visit_pound(&mut self, _span: Span) {
+            self.0.push("pound".to_string());
+        }
+
+        fn visit_option(&mut self, key: &str, body: &[AstNode]) {
+            self.0.push(format!("option {}", key));
+            walk_nodes(self, body);
+        }
+
+        fn visit_html_tag(
+            &mut self,
+            name: &str,
+            _attributes: &[Attribute],
+            _numbered: bool,
+            _self_closing: bool,
+            children: &[AstNode],
+            _span: Span,
+        ) {
+            self.0.push(format!("tag {}", name));
+            walk_nodes(self, children);
+        }
+    }
+
+    #[test]
+    fn visits_nested_nodes_in_order() {
+        let node = Parser::new(NESTED).parse().unwrap();
+        let mut trace = Trace::default();
+        trace.visit_node(&node);
+        assert_eq!(
+            trace.0,
+            [
+                "option female",
+                "tag b",
+                "variable name",
+                "text \" has \"",
+                "option one",
+                "pound",
+                "text \" item\"",
+                "option other",
+                "pound",
+                "text \" items\"",
+                "option other",
+                "variable name",
+            ]
+        );
+    }
+
+    #[test]
+    fn visits_what_an_error_recovered() {
+        let (node, _) = Parser::new("{n, plural, one {{a}}} {b").parse_recovering();
+        let mut trace = Trace::default();
+        trace.visit_node(&node);
+        assert_eq!(
+            trace.0,
+            ["option one", "variable a", "text \" \"", "variable b"]
+        );
+    }
+
+    /// Stops at options, so nothing inside them is visited.
+    struct TopLevel(Vec<String>);
+
+    impl Visit for TopLevel {
+        fn visit_variable(&mut self, name: &str, _span: Span) {
+            self.0.push(name.to_string());
+        }
+
+        fn visit_select(&mut self, variable: &str, _options: &Options, _span: Span) {
+            self.0.push(variable.to_string());
+        }
+    }
+
+    #[test]
+    fn overrides_decide_whether_to_descend() {
+        let node = Parser::new(&format!("{{first}} {}", NESTED))
+            .parse()
+            .unwrap();
+        let mut top = TopLevel(Vec::new());
+        top.visit_node(&node);
+        assert_eq!(top.0, ["first", "gender"]);
+    }
+
+    /// Renames one argument wherever it is used.
+    struct Rename(&'static str, &'static str);
+
+    impl Fold for Rename {
+        fn fold_variable(&mut self, name: String, span: Span) -> AstNode {
+            AstNode::Variable(self.rename(name), span)
+        }
+
+        fn fold_plural(
+            &mut self,
+            variable: String,
+            offset: u32,
+            options: Options,
+            span: Span,
+        ) -> AstNode {
+            AstNode::Plural {
+                variable: self.rename(variable),
+                offset,
+                options: rebuild_options(self, options),
+                span,
+            }
+        }
+    }
+
+    impl Rename {
+        fn rename(&self, name: String) -> String {
+            if name == self.0 {
+                self.1.to_string()
+            } else {
+                name
+            }
+        }
+    }
+
+    /// Folds with the defaults only.
+    struct Identity;
+
+    impl Fold for Identity {}
+
+    #[test]
+    fn default_fold_rebuilds_the_same_tree() {
+        for message in [NESTED, "<0>a</0><br/> {n, number} {d, date, short}", "{a"] {
+            let (node, _) = Parser::new(message).parse_recovering();
+            assert_eq!(Identity.fold_node(node.clone()), node);
+        }
+    }
+
+    #[test]
+    fn folds_nested_nodes() {
+        let node = Parser::new(NESTED).parse().unwrap();
+        let renamed = Rename("count", "n").fold_node(node.clone());
+        assert_eq!(
+            Printer::new().print(&renamed),
+            NESTED.replace("{count,", "{n,")
+        );
+        let renamed = Rename("name", "who").fold_node(node);
+        assert_eq!(
+            Printer::new().print(&renamed),
+            NESTED.replace("{name}", "{who}")
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! XLIFF 1.2 and 2.0 catalogs.
+//!
+//! Both versions hold translation units with a source text, an optional
+//! target text and notes for translators:
+//!
+//! ```text
+//! <xliff version="1.2">
+//!   <file source-language="en" target-language="fr">
+//!     <body>
+//!       <trans-unit id="greeting">
+//!         <source>Hello <x id="name"/></source>
+//!         <target>Bonjour <x id="name"/></target>
+//!         <note>Shown after login.</note>
+//!       </trans-unit>
+//!     </body>
+//!   </file>
+//! </xliff>
+//! ```
+//!
+//! XLIFF 2.0 calls them `unit`, splits their text into `segment`s and puts
+//! the languages on the `xliff` element as `srcLang` and `trgLang`.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+/// A translation unit of an XLIFF file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct XliffUnit {
+    /// The `resname` (1.2) or `name` (2.0) of the unit, or else its `id`.
+    pub key: String,
+    pub source_language: Option<String>,
+    pub target_language: Option<String>,
+    pub source: String,
+    /// The translation, if the unit has one.
+    pub target: Option<String>,
+    pub notes: Vec<String>,
+}
+
+/// Where text inside a unit goes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Sink {
+    Source,
+    Target,
+    Note,
+}
+
+/// Reads the translation units of an XLIFF 1.2 or 2.0 document.
+///
+/// Inline placeholders are kept as the text they stand for: the
+/// `equiv-text` of an `<x/>` or the `disp` or `equiv` of a 2.0 `<ph/>`, and
+/// `{id}` if they have none. The content of a 1.2 `<ph>`, which is the
+/// original code, is kept as is, and other inline markup such as `<g>` or
+/// `<pc>` is dropped around its text.
+pub fn parse(content: &str) -> Result<Vec<XliffUnit>, quick_xml::Error> {
+    let mut reader = Reader::from_str(content);
+    let mut units = Vec::new();
+    // The languages of the `xliff` element and of the current `file`.
+    let mut document_languages = (None, None);
+    let mut file_languages = (None, None);
+    let mut unit: Option<XliffUnit> = None;
+    let mut sink = None;
+    // `alt-trans` holds suggestions with their own source and target.
+    let mut alternatives = 0;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Start(element) | Event::Empty(element) => {
+                let empty = matches!(event, Event::Empty(_));
+                match element.local_name().as_ref() {
+                    b"xliff" => {
+                        document_languages = languages(element, b"srcLang", b"trgLang")?;
+                        file_languages = document_languages.clone();
+                    }
+                    b"file" => {
+                        let (source, target) =
+                            languages(element, b"source-language", b"target-language")?;
+                        file_languages = (
+                            source.or_else(|| document_languages.0.clone()),
+                            target.or_else(|| document_languages.1.clone()),
+                        );
+                    }
+                    b"trans-unit" | b"unit" if !empty => {
+                        let key = match attribute(element, b"resname")? {
+                            Some(key) => Some(key),
+                            None => match attribute(element, b"name")? {
+                                Some(key) => Some(key),
+                                None => attribute(element, b"id")?,
+                            },
+                        };
+                        unit = Some(XliffUnit {
+                            key: key.unwrap_or_default(),
+                            source_language: file_languages.0.clone(),
+                            target_language: file_languages.1.clone(),
+                            ..XliffUnit::default()
+                        });
+                    }
+                    b"alt-trans" if !empty => alternatives += 1,
+                    name => {
+                        let Some(current) = unit.as_mut().filter(|_| alternatives == 0) else {
+                            continue;
+                        };
+                        match (name, sink) {
+                            (b"source", None) if !empty => sink = Some(Sink::Source),
+                            (b"target", None) if !empty => {
+                                current.target.get_or_insert_default();
+                                sink = Some(Sink::Target);
+                            }
+                            (b"note", None) if !empty => {
+                                current.notes.push(String::new());
+                                sink = Some(Sink::Note);
+                            }
+                            (b"x" | b"ph", Some(Sink::Source | Sink::Target)) if empty => {
+                                let placeholder = placeholder(element)?;
+                                push(current, sink, &placeholder);
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"trans-unit" | b"unit" => {
+                    units.extend(unit.take().filter(|unit| !unit.key.is_empty()));
+                    sink = None;
+                }
+                b"file" => file_languages = document_languages.clone(),
+                b"alt-trans" => alternatives -= 1,
+                b"source" | b"target" | b"note" if alternatives == 0 => sink = None,
+                _ => {}
+            },
+            Event::Text(text) => {
+                if let Some(current) = unit.as_mut() {
+                    push(current, sink, &text.unescape()?);
+                }
+            }
+            Event::CData(text) => {
+                if let Some(current) = unit.as_mut() {
+                    push(current, sink, &String::from_utf8_lossy(text));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(units)
+}
+
+fn push(unit: &mut XliffUnit, sink: Option<Sink>, text: &str) {
+    match sink {
+        Some(Sink::Source) => unit.source.push_str(text),
+        Some(Sink::Target) => unit.target.get_or_insert_default().push_str(text),
+        Some(Sink::Note) => {
+            if let Some(note) = unit.notes.last_mut() {
+                note.push_str(text);
+            }
+        }
+        None => {}
+    }
+}
+
+fn placeholder(element: &BytesStart) -> Result<String, quick_xml::Error> {
+    for name in [&b"equiv-text"[..], b"disp", b"equiv"] {
+        if let Some(text) = attribute(element, name)? {
+            return Ok(text);
+        }
+    }
+    let id = attribute(element, b"id")?.unwrap_or_default();
+    Ok(format!("{{{}}}", id))
+}
+
+fn languages(
+    element: &BytesStart,
+    source: &[u8],
+    target: &[u8],
+) -> Result<(Option<String>, Option<String>), quick_xml::Error> {
+    Ok((attribute(element, source)?, attribute(element, target)?))
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, quick_xml::Error> {
+    match element.try_get_attribute(name)? {
+        Some(attribute) => Ok(Some(attribute.unescape_value()?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(key: &str, source: &str, target: Option<&str>, notes: &[&str]) -> XliffUnit {
+        XliffUnit {
+            key: key.to_string(),
+            source_language: Some("en".to_string()),
+            target_language: Some("fr".to_string()),
+            source: source.to_string(),
+            target: target.map(str::to_string),
+            notes: notes.iter().map(|note| note.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn reads_version_1_2() {
+        let units = parse(
+            r#"<?xml version="1.0"?>
+            <xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+              <file source-language="en" target-language="fr" datatype="plaintext">
+                <body>
+                  <trans-unit id="1" resname="greeting">
+                    <source>Hello &amp; welcome</source>
+                    <target>Bonjour et bienvenue</target>
+                    <note>Shown after login.</note>
+                    <alt-trans><target>Salut</target></alt-trans>
+                  </trans-unit>
+                  <trans-unit id="farewell">
+                    <source>Bye</source>
+                  </trans-unit>
+                </body>
+              </file>
+            </xliff>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            units,
+            [
+                unit(
+                    "greeting",
+                    "Hello & welcome",
+                    Some("Bonjour et bienvenue"),
+                    &["Shown after login."]
+                ),
+                unit("farewell", "Bye", None, &[]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_version_2_0() {
+        let units = parse(
+            r#"<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="fr">
+              <file id="f1">
+                <unit id="u1" name="cart.title">
+                  <notes><note category="context">Page heading.</note></notes>
+                  <segment><source>Your </source><target>Votre </target></segment>
+                  <segment><source>cart</source><target>panier</target></segment>
+                </unit>
+              </file>
+            </xliff>"#,
+        )
+        .unwrap();
+        assert_eq!(
+            units,
+            [unit(
+                "cart.title",
+                "Your cart",
+                Some("Votre panier"),
+                &["Page heading."]
+            )]
+        );
+    }
+
+    #[test]
+    fn converts_inline_placeholders() {
+        let units = parse(
+            r#"<xliff version="1.2"><file source-language="en" target-language="fr"><body>
+              <trans-unit id="a">
+                <source>Hi <x id="name"/>, <x id="n" equiv-text="{count}"/> <g id="1">new</g></source>
+                <target>Salut <ph id="2">%s</ph></target>
+              </trans-unit>
+            </body></file></xliff>"#,
+        )
+        .unwrap();
+        assert_eq!(units[0].source, "Hi {name}, {count} new");
+        assert_eq!(units[0].target.as_deref(), Some("Salut %s"));
+
+        let units = parse(
+            r#"<xliff version="2.0" srcLang="en"><file id="f"><unit id="b"><segment>
+              <source><ph id="1" disp="{user}"/> paid <ph id="2" equiv="{amount}"/> <ph id="3"/> <pc id="4">now</pc></source>
+            </segment></unit></file></xliff>"#,
+        )
+        .unwrap();
+        assert_eq!(units[0].source, "{user} paid {amount} {3} now");
+        assert_eq!(units[0].target_language, None);
+    }
+}
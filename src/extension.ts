@@ -19,7 +19,7 @@ export async function activate(context: ExtensionContext) {
   context.subscriptions.push(
     workspace.onDidChangeConfiguration((e) => {
       if (
-        ['typedkey.translationsDir', 'typedkey.logLevel', 'typedkey.path'].some(
+        ['typedkey.translationsDir', 'typedkey.translationsPattern', 'typedkey.dialect', 'typedkey.sourceLocale', 'typedkey.nsSeparator', 'typedkey.defaultNS', 'typedkey.fallbackNS', 'typedkey.logLevel', 'typedkey.path'].some(
           s => e.affectsConfiguration(s),
         )
       ) {
@@ -79,6 +79,12 @@ async function createClient(
     synchronize: { configurationSection: 'typedkey' },
     initializationOptions: {
       translationsDir: config.get('translationsDir'),
+      translationsPattern: config.get('translationsPattern') || undefined,
+      dialect: config.get('dialect'),
+      sourceLocale: config.get('sourceLocale'),
+      nsSeparator: config.get('nsSeparator'),
+      defaultNS: config.get('defaultNS') || undefined,
+      fallbackNS: config.get('fallbackNS'),
       logLevel: config.get('logLevel'),
     },
     outputChannel,